@@ -0,0 +1,346 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{string::String, vec::Vec};
+
+/// IDs of game entities, grouped by kind
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct AssetIds {
+    pub palettes: Vec<String>,
+    pub rooms: Vec<String>,
+    pub tiles: Vec<String>,
+    pub sprites: Vec<String>,
+    pub items: Vec<String>,
+    pub dialogues: Vec<String>,
+    pub endings: Vec<String>,
+    pub variables: Vec<String>,
+}
+
+impl AssetIds {
+    pub fn is_empty(&self) -> bool {
+        self.palettes.is_empty()
+            && self.rooms.is_empty()
+            && self.tiles.is_empty()
+            && self.sprites.is_empty()
+            && self.items.is_empty()
+            && self.dialogues.is_empty()
+            && self.endings.is_empty()
+            && self.variables.is_empty()
+    }
+}
+
+/// adds the ID to the list unless it is already there
+fn insert(ids: &mut Vec<String>, id: &str) {
+    if !ids.iter().any(|i| i == id) {
+        ids.push(id.to_owned());
+    }
+}
+
+impl Game {
+    /// IDs of everything that can be reached from the avatar's starting room:
+    /// rooms through exits, and whatever those rooms, the sprites and items in them
+    /// and their dialogue scripts refer to
+    fn used_assets(&self) -> AssetIds {
+        let mut used = AssetIds::default();
+        insert(&mut used.sprites, "A");
+        let start = self
+            .get_avatar()
+            .and_then(|avatar| avatar.room_id.as_ref())
+            .filter(|id| self.get_room(id).is_some());
+        match start {
+            Some(id) => insert(&mut used.rooms, id),
+            // without a starting room, there is no telling which rooms can be reached
+            None => {
+                for room in &self.rooms {
+                    insert(&mut used.rooms, &room.id);
+                }
+            }
+        }
+
+        // each pass can reach more rooms, sprites, items and dialogues
+        loop {
+            let before = used.clone();
+            self.use_rooms(&mut used);
+            self.use_sprites_and_items(&mut used);
+            self.use_scripts(&mut used);
+            if used == before {
+                return used;
+            }
+        }
+    }
+
+    fn use_rooms(&self, used: &mut AssetIds) {
+        let rooms: Vec<&Room> = self
+            .rooms
+            .iter()
+            .filter(|room| used.rooms.contains(&room.id))
+            .collect();
+        for room in rooms {
+            for exit in &room.exits {
+                insert(&mut used.rooms, &exit.exit.room_id);
+                if let Some(id) = &exit.dialogue_id {
+                    insert(&mut used.dialogues, id);
+                }
+            }
+            for item in &room.items {
+                insert(&mut used.items, &item.id);
+            }
+            match &room.palette_id {
+                Some(id) => insert(&mut used.palettes, id),
+                // very early rooms don't specify a palette, so the first one is used
+                None => {
+                    if let Some(palette) = self.palettes.first() {
+                        insert(&mut used.palettes, &palette.id);
+                    }
+                }
+            }
+            for id in &room.tiles {
+                insert(&mut used.tiles, id);
+            }
+            for id in room.walls.iter().flatten() {
+                insert(&mut used.tiles, id);
+            }
+            for ending in &room.endings {
+                insert(&mut used.endings, &ending.id);
+                // since Bitsy 8, endings are dialogues
+                insert(&mut used.dialogues, &ending.id);
+            }
+            for sprite in &self.sprites {
+                if sprite.room_id.as_ref() == Some(&room.id) {
+                    insert(&mut used.sprites, &sprite.id);
+                }
+            }
+        }
+    }
+
+    fn use_sprites_and_items(&self, used: &mut AssetIds) {
+        for sprite in self.sprites.iter().filter(|s| used.sprites.contains(&s.id)) {
+            if let Some(id) = self.sprite_dialogue_id(sprite) {
+                insert(&mut used.dialogues, id);
+            }
+            // inventory lines are `<item ID> <count>`
+            for line in &sprite.items {
+                insert(&mut used.items, line.split(' ').next().unwrap_or(line));
+            }
+        }
+        for item in self.items.iter().filter(|i| used.items.contains(&i.id)) {
+            if let Some(id) = &item.dialogue_id {
+                insert(&mut used.dialogues, id);
+            }
+        }
+    }
+
+    fn use_scripts(&self, used: &mut AssetIds) {
+        let mut references = ScriptReferences::default();
+        for dialogue in &self.dialogues {
+            if used.dialogues.contains(&dialogue.id)
+                && let Ok(script) = dialogue.script()
+            {
+                references.script(&script, &dialogue.id);
+            }
+        }
+        for ending in &self.endings {
+            if used.endings.contains(&ending.id)
+                && let Ok(script) = ending.script()
+            {
+                references.script(&script, &ending.id);
+            }
+        }
+
+        for item in &references.items {
+            // items can be referred to by name as well as by ID
+            match self
+                .items
+                .iter()
+                .find(|i| i.name.as_ref() == Some(&item.item_id))
+            {
+                Some(named) if self.get_item(&item.item_id).is_none() => {
+                    insert(&mut used.items, &named.id)
+                }
+                _ => insert(&mut used.items, &item.item_id),
+            }
+        }
+        for exit in &references.exits {
+            insert(&mut used.rooms, &exit.room_id);
+        }
+        for end in &references.ends {
+            insert(&mut used.endings, &end.ending_id);
+            insert(&mut used.dialogues, &end.ending_id);
+        }
+        for usage in &references.variables {
+            insert(&mut used.variables, &usage.variable);
+        }
+    }
+
+    /// everything the player can never reach from the avatar's starting room:
+    /// rooms without a way in from there, sprites and items that aren't in those rooms
+    /// or carried, and the palettes, tiles, dialogues, endings and variables that
+    /// only unreachable things use. the avatar always counts as used.
+    pub fn unused_assets(&self) -> AssetIds {
+        let used = self.used_assets();
+        let unused = |ids: Vec<String>, used: &[String]| -> Vec<String> {
+            ids.into_iter().filter(|id| !used.contains(id)).collect()
+        };
+
+        AssetIds {
+            palettes: unused(self.palette_ids(), &used.palettes),
+            rooms: unused(self.room_ids(), &used.rooms),
+            tiles: unused(self.tile_ids(), &used.tiles),
+            sprites: unused(self.sprite_ids(), &used.sprites),
+            items: unused(self.item_ids(), &used.items),
+            dialogues: unused(self.dialogue_ids(), &used.dialogues),
+            endings: unused(self.ending_ids(), &used.endings),
+            variables: unused(self.variable_ids(), &used.variables),
+        }
+    }
+
+    /// removes everything reported by `unused_assets()` and returns the removed IDs
+    pub fn prune_unused(&mut self) -> AssetIds {
+        let unused = self.unused_assets();
+        self.palettes.retain(|p| !unused.palettes.contains(&p.id));
+        self.rooms.retain(|r| !unused.rooms.contains(&r.id));
+        self.tiles.retain(|t| !unused.tiles.contains(&t.id));
+        self.sprites.retain(|s| !unused.sprites.contains(&s.id));
+        self.items.retain(|i| !unused.items.contains(&i.id));
+        self.dialogues.retain(|d| !unused.dialogues.contains(&d.id));
+        self.endings.retain(|e| !unused.endings.contains(&e.id));
+        self.variables.retain(|v| !unused.variables.contains(&v.id));
+        unused
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    /// the default game, with both items and the variable in use
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 3, y: 3 },
+            id: "0".to_string(),
+        });
+        game.dialogues[0].contents = "{print a} and {item \"key\"}".to_string();
+        game
+    }
+
+    fn exit_to(room_id: &str) -> ExitInstance {
+        ExitInstance {
+            position: Position { x: 1, y: 1 },
+            exit: Exit {
+                room_id: room_id.to_string(),
+                position: Position { x: 2, y: 2 },
+                effect: Transition::None,
+            },
+            transition: None,
+            dialogue_id: None,
+        }
+    }
+
+    #[test]
+    fn unused_assets_default_game() {
+        assert!(game().unused_assets().is_empty());
+
+        // the default game doesn't place its items or use its variable,
+        // so the item dialogues can't be reached either
+        let expected = AssetIds {
+            items: vec!["0".to_string(), "1".to_string()],
+            dialogues: vec!["1".to_string(), "2".to_string()],
+            variables: vec!["a".to_string()],
+            ..AssetIds::default()
+        };
+        assert_eq!(mock::game_default().unused_assets(), expected);
+    }
+
+    #[test]
+    fn unused_rooms_and_sprites() {
+        let mut game = game();
+        for id in ["1", "2"] {
+            let mut room = mock::room();
+            room.id = id.to_string();
+            game.rooms.push(room);
+        }
+        game.dialogues[1].contents = "{exit \"2\" 1 1}".to_string();
+        let mut sprite = mock::sprite();
+        sprite.id = "b".to_string();
+        sprite.room_id = None;
+        game.sprites.push(sprite);
+
+        let unused = game.unused_assets();
+        assert_eq!(unused.rooms, vec!["1".to_string()]);
+        assert_eq!(unused.sprites, vec!["b".to_string()]);
+        assert_eq!(game.prune_unused(), unused);
+        assert_eq!(game.room_ids(), vec!["0".to_string(), "2".to_string()]);
+    }
+
+    #[test]
+    fn unreachable_rooms() {
+        let mut game = game();
+        // 1 <-> 2 -> 3 can't be reached from the avatar's room, 0 -> 4 can
+        for (id, to) in [("1", "2"), ("2", "1"), ("3", ""), ("4", "")] {
+            let mut room = mock::room();
+            room.id = id.to_string();
+            room.exits.clear();
+            if !to.is_empty() {
+                room.exits.push(exit_to(to));
+            }
+            game.rooms.push(room);
+        }
+        game.rooms[2].exits.push(exit_to("3"));
+        game.rooms[0].exits.push(exit_to("4"));
+        let mut sprite = mock::sprite();
+        sprite.id = "b".to_string();
+        sprite.room_id = Some("2".to_string());
+        game.sprites.push(sprite);
+
+        let unused = game.prune_unused();
+        assert_eq!(unused.rooms, vec!["1", "2", "3"]);
+        assert_eq!(unused.sprites, vec!["b"]);
+        assert_eq!(game.room_ids(), vec!["0", "4"]);
+        assert!(game.unused_assets().is_empty());
+    }
+
+    #[test]
+    fn unused_assets() {
+        let mut game = game();
+        let mut tile = mock::tile_default();
+        tile.id = "b".to_string();
+        game.tiles.push(tile);
+        game.palettes.push(Palette {
+            id: "1".to_string(),
+            name: None,
+            colours: Vec::new(),
+        });
+        game.dialogues.push(Dialogue {
+            id: "3".to_string(),
+            contents: "orphan".to_string(),
+            name: None,
+        });
+        game.endings.push(Ending {
+            id: "4".to_string(),
+            dialogue: "the end".to_string(),
+        });
+
+        let expected = AssetIds {
+            palettes: vec!["1".to_string()],
+            tiles: vec!["b".to_string()],
+            dialogues: vec!["3".to_string()],
+            endings: vec!["4".to_string()],
+            ..AssetIds::default()
+        };
+        assert_eq!(game.unused_assets(), expected);
+
+        assert_eq!(game.prune_unused(), expected);
+        assert_eq!(game, self::game());
+    }
+
+    #[test]
+    fn implicit_sprite_dialogue_is_used() {
+        let mut game = game();
+        game.sprites[1].dialogue_id = None;
+        game.dialogues[0].id = "a".to_string();
+        assert!(game.unused_assets().dialogues.is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use crate::*;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// a part of the game data format that only some Bitsy versions support
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Feature {
+    /// `ROOM` instead of `SET`
+    RoomKeyword,
+    /// `! ROOM_FORMAT 1`, comma-separated tile IDs in rooms
+    CommaRoomFormat,
+    /// `WAL true` on tiles instead of wall lists on rooms
+    TileWalls,
+    /// `COL` on tiles, sprites and items
+    ColourIds,
+    /// `DEFAULT_FONT` and `FONT` data
+    Fonts,
+    /// `FX` on exits
+    ExitTransitions,
+    /// `DLG` on exits
+    ExitDialogue,
+    /// `TEXT_DIRECTION`
+    TextDirection,
+    /// `DLG` on sprites instead of dialogue sharing the sprite's ID
+    SpriteDialogueIds,
+    /// `! VER_MAJ`, `! VER_MIN`, `! DLG_COMPAT` and `! TXT_MODE`
+    HeaderFlags,
+    /// room endings that show a `DLG` instead of an `END`
+    DialogueEndings,
+    /// `TUNE` and `BLIP`, which this crate doesn't parse yet
+    TunesAndBlips,
+    /// 16x16 images, from the bitsy-hd fork
+    HdImages,
+}
+
+impl Feature {
+    pub const ALL: [Feature; 13] = [
+        Feature::RoomKeyword,
+        Feature::CommaRoomFormat,
+        Feature::TileWalls,
+        Feature::ColourIds,
+        Feature::Fonts,
+        Feature::ExitTransitions,
+        Feature::ExitDialogue,
+        Feature::TextDirection,
+        Feature::SpriteDialogueIds,
+        Feature::HeaderFlags,
+        Feature::DialogueEndings,
+        Feature::TunesAndBlips,
+        Feature::HdImages,
+    ];
+
+    /// the first Bitsy version with the feature. `None` if no release has it.
+    pub fn since(&self) -> Option<Version> {
+        let (major, minor) = match self {
+            Feature::RoomKeyword => (3, 0),
+            Feature::CommaRoomFormat | Feature::TileWalls | Feature::SpriteDialogueIds => (4, 0),
+            Feature::ColourIds => (5, 0),
+            Feature::Fonts | Feature::ExitTransitions | Feature::ExitDialogue => (6, 0),
+            Feature::TextDirection => (7, 0),
+            Feature::HeaderFlags | Feature::DialogueEndings | Feature::TunesAndBlips => (8, 0),
+            Feature::HdImages => return None,
+        };
+        Some(Version { major, minor })
+    }
+
+    /// e.g. "Bitsy 5.0 doesn't support fonts"
+    pub(crate) fn unsupported_by(&self, version: Version) -> String {
+        format!("Bitsy {} doesn't support {}", version, self)
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Feature::RoomKeyword => "ROOM keyword",
+            Feature::CommaRoomFormat => "comma room format",
+            Feature::TileWalls => "tile walls",
+            Feature::ColourIds => "colour IDs",
+            Feature::Fonts => "fonts",
+            Feature::ExitTransitions => "exit transitions",
+            Feature::ExitDialogue => "exit dialogue",
+            Feature::TextDirection => "text direction",
+            Feature::SpriteDialogueIds => "sprite dialogue IDs",
+            Feature::HeaderFlags => "header flags",
+            Feature::DialogueEndings => "dialogue endings",
+            Feature::TunesAndBlips => "tunes and blips",
+            Feature::HdImages => "HD images",
+        }
+    }
+}
+
+impl fmt::Display for Feature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+impl Version {
+    pub fn supports(&self, feature: Feature) -> bool {
+        feature.since().is_some_and(|since| *self >= since)
+    }
+
+    pub fn features(&self) -> Vec<Feature> {
+        Feature::ALL
+            .into_iter()
+            .filter(|feature| self.supports(*feature))
+            .collect()
+    }
+}
+
+impl Game {
+    /// IDs of the dialogues that rooms use as endings, as Bitsy 8 does
+    pub(crate) fn dialogue_endings(&self) -> Vec<String> {
+        let mut ids: Vec<String> = Vec::new();
+        for ending in self.rooms.iter().flat_map(|room| &room.endings) {
+            if !self.endings.iter().any(|e| e.id == ending.id)
+                && self.dialogues.iter().any(|d| d.id == ending.id)
+                && !ids.contains(&ending.id)
+            {
+                ids.push(ending.id.clone());
+            }
+        }
+        ids
+    }
+
+    /// the version-specific features the game's data uses.
+    /// the room keyword is left out, as every game uses one or the other.
+    pub fn used_features(&self) -> Vec<Feature> {
+        let exits = || self.rooms.iter().flat_map(|room| &room.exits);
+        let frames = || {
+            let tiles = self.tiles.iter().flat_map(|t| &t.animation_frames);
+            let sprites = self.sprites.iter().flat_map(|s| &s.animation_frames);
+            let items = self.items.iter().flat_map(|i| &i.animation_frames);
+            tiles.chain(sprites).chain(items)
+        };
+        let colour_ids = self.tiles.iter().any(|t| t.colour_id.is_some())
+            || self.sprites.iter().any(|s| s.colour_id.is_some())
+            || self.items.iter().any(|i| i.colour_id.is_some());
+
+        Feature::ALL
+            .into_iter()
+            .filter(|feature| match feature {
+                Feature::RoomKeyword | Feature::TunesAndBlips => false,
+                Feature::CommaRoomFormat => self.room_format == Some(RoomFormat::CommaSeparated),
+                Feature::TileWalls => self.tiles.iter().any(|t| t.wall.is_some()),
+                Feature::ColourIds => colour_ids,
+                Feature::Fonts => self.font != Font::AsciiSmall,
+                Feature::ExitTransitions => exits().any(|e| {
+                    e.transition
+                        .as_ref()
+                        .is_some_and(|t| *t != Transition::None)
+                }),
+                Feature::ExitDialogue => exits().any(|e| e.dialogue_id.is_some()),
+                Feature::TextDirection => self.text_direction == TextDirection::RightToLeft,
+                Feature::SpriteDialogueIds => self.sprites.iter().any(|s| s.dialogue_id.is_some()),
+                Feature::HeaderFlags => !self.flags.is_empty(),
+                Feature::DialogueEndings => !self.dialogue_endings().is_empty(),
+                Feature::HdImages => frames().any(|frame| frame.dimension > 8),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::vec;
+
+    #[test]
+    fn supports() {
+        let version = Version { major: 6, minor: 5 };
+        assert!(version.supports(Feature::ExitDialogue));
+        assert!(!version.supports(Feature::TextDirection));
+        assert!(
+            !Version {
+                major: 8,
+                minor: 14
+            }
+            .supports(Feature::HdImages)
+        );
+        assert_eq!(
+            Version { major: 3, minor: 2 }.features(),
+            vec![Feature::RoomKeyword]
+        );
+    }
+
+    #[test]
+    fn used_features() {
+        assert_eq!(
+            mock::game_default().used_features(),
+            vec![Feature::CommaRoomFormat, Feature::SpriteDialogueIds]
+        );
+        let game = Game::from(include_str!("test-resources/arabic.bitsy")).unwrap();
+        assert_eq!(
+            game.used_features(),
+            vec![
+                Feature::CommaRoomFormat,
+                Feature::Fonts,
+                Feature::TextDirection,
+                Feature::SpriteDialogueIds
+            ]
+        );
+    }
+}
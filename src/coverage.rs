@@ -0,0 +1,329 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// the characters a font can draw, as inclusive ranges
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct CharacterSet {
+    ranges: Vec<(char, char)>,
+}
+
+const ASCII: (char, char) = (' ', '~');
+const LATIN: [(char, char); 2] = [('\u{a0}', '\u{24f}'), ('\u{2000}', '\u{206f}')];
+const GREEK_CYRILLIC: [(char, char); 2] = [('\u{370}', '\u{3ff}'), ('\u{400}', '\u{4ff}')];
+const ARABIC: [(char, char); 4] = [
+    ('\u{600}', '\u{6ff}'),
+    ('\u{750}', '\u{77f}'),
+    ('\u{fb50}', '\u{fdff}'),
+    ('\u{fe70}', '\u{feff}'),
+];
+const ASIAN: [(char, char); 5] = [
+    ('\u{3000}', '\u{30ff}'),
+    ('\u{3400}', '\u{4dbf}'),
+    ('\u{4e00}', '\u{9fff}'),
+    ('\u{ac00}', '\u{d7af}'),
+    ('\u{ff00}', '\u{ffef}'),
+];
+
+/// the built-in fonts, in the order they are suggested
+const SUGGESTIONS: [Font; 4] = [
+    Font::AsciiSmall,
+    Font::UnicodeEuropeanSmall,
+    Font::Arabic,
+    Font::UnicodeAsian,
+];
+
+impl CharacterSet {
+    fn from_ranges(ranges: &[&[(char, char)]]) -> CharacterSet {
+        CharacterSet {
+            ranges: ranges.concat(),
+        }
+    }
+
+    /// the characters in a custom font's `FONT` data, from its `CHAR` lines
+    pub fn from_font_data(data: &str) -> CharacterSet {
+        let mut chars: Vec<char> = data
+            .lines()
+            .filter_map(|line| line.strip_prefix("CHAR "))
+            .filter_map(|code| code.trim().parse::<u32>().ok())
+            .filter_map(char::from_u32)
+            .collect();
+        chars.sort();
+        chars.dedup();
+
+        let mut ranges: Vec<(char, char)> = Vec::new();
+        for c in chars {
+            match ranges.last_mut() {
+                Some((_, end)) if u32::from(*end) + 1 == u32::from(c) => *end = c,
+                _ => ranges.push((c, c)),
+            }
+        }
+        CharacterSet { ranges }
+    }
+
+    /// line breaks and other control characters are never drawn, so always count
+    pub fn contains(&self, c: char) -> bool {
+        c.is_control()
+            || self
+                .ranges
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(&c))
+    }
+
+    /// the characters in the text that aren't in the set, without repeats
+    pub fn missing(&self, text: &str) -> Vec<char> {
+        let mut missing = Vec::new();
+        for c in text.chars() {
+            if !self.contains(c) && !missing.contains(&c) {
+                missing.push(c);
+            }
+        }
+        missing
+    }
+}
+
+impl Font {
+    /// the characters a built-in font can draw. `None` for custom fonts.
+    pub fn characters(&self) -> Option<CharacterSet> {
+        let ascii: &[(char, char)] = &[ASCII];
+        Some(match self {
+            Font::AsciiSmall => CharacterSet::from_ranges(&[ascii]),
+            Font::UnicodeEuropeanSmall | Font::UnicodeEuropeanLarge => {
+                CharacterSet::from_ranges(&[ascii, &LATIN, &GREEK_CYRILLIC])
+            }
+            Font::Arabic => CharacterSet::from_ranges(&[ascii, &ARABIC]),
+            Font::UnicodeAsian => CharacterSet::from_ranges(&[ascii, &LATIN, &ASIAN]),
+            Font::Custom => return None,
+        })
+    }
+
+    /// the first built-in font that can draw all of the text
+    pub fn suggest(text: &str) -> Option<Font> {
+        SUGGESTIONS.into_iter().find(|font| {
+            font.characters()
+                .is_some_and(|characters| characters.missing(text).is_empty())
+        })
+    }
+}
+
+/// the text of a script that is shown to the player: its text and the strings
+/// passed to `{say}` and `{print}`, without the rest of its code.
+/// a script with syntax errors is checked as it is.
+fn shown_text(source: &str) -> String {
+    let Ok(script) = Script::parse(source) else {
+        return source.to_owned();
+    };
+    let mut text = String::new();
+    script.visit(&mut |node| match &node.kind {
+        NodeKind::Text(part) => text.push_str(part),
+        NodeKind::Function { name, arguments } if name == "say" || name == "print" => {
+            for argument in arguments {
+                if let Expression::String(string) = argument {
+                    text.push_str(string);
+                }
+            }
+        }
+        _ => {}
+    });
+    text
+}
+
+/// text containing characters the game's font can't draw
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct UnsupportedText {
+    pub entity: EntityKind,
+    pub id: String,
+    pub field: &'static str,
+    pub characters: Vec<char>,
+    /// a built-in font that can draw the whole text, if there is one
+    pub suggestion: Option<Font>,
+}
+
+impl UnsupportedText {
+    pub fn to_diagnostic(&self) -> Diagnostic {
+        let characters: Vec<String> = self.characters.iter().map(|c| format!("{:?}", c)).collect();
+        let mut message = format!("the font can't draw {}", characters.join(", "));
+        if let Some(Ok(font)) = self.suggestion.as_ref().map(Font::to_string) {
+            message.push_str(&format!("; try {}", font));
+        }
+        Diagnostic {
+            severity: Severity::Warning,
+            entity: self.entity,
+            id: self.id.clone(),
+            field: self.field,
+            message,
+        }
+    }
+}
+
+impl Game {
+    /// the characters the game's font can draw. a custom font without
+    /// font data is assumed to draw everything, so gives `None`.
+    pub fn font_characters(&self) -> Option<CharacterSet> {
+        match self.font {
+            Font::Custom => self.font_data.as_deref().map(CharacterSet::from_font_data),
+            _ => self.font.characters(),
+        }
+    }
+
+    /// the title, dialogues, endings and sprite, item and room names
+    /// that the game's font can't draw
+    pub fn unsupported_text(&self) -> Vec<UnsupportedText> {
+        let Some(characters) = self.font_characters() else {
+            return Vec::new();
+        };
+
+        let mut texts: Vec<(EntityKind, &str, &'static str, String)> =
+            vec![(EntityKind::Game, "", "name", self.name.clone())];
+        for room in &self.rooms {
+            if let Some(name) = &room.name {
+                texts.push((EntityKind::Room, &room.id, "name", name.clone()));
+            }
+        }
+        for sprite in &self.sprites {
+            if let Some(name) = &sprite.name {
+                texts.push((EntityKind::Sprite, &sprite.id, "name", name.clone()));
+            }
+        }
+        for item in &self.items {
+            if let Some(name) = &item.name {
+                texts.push((EntityKind::Item, &item.id, "name", name.clone()));
+            }
+        }
+        for dialogue in &self.dialogues {
+            texts.push((
+                EntityKind::Dialogue,
+                &dialogue.id,
+                "contents",
+                shown_text(&dialogue.contents),
+            ));
+        }
+        for ending in &self.endings {
+            texts.push((
+                EntityKind::Ending,
+                &ending.id,
+                "dialogue",
+                shown_text(&ending.dialogue),
+            ));
+        }
+
+        texts
+            .into_iter()
+            .filter_map(|(entity, id, field, text)| {
+                let missing = characters.missing(&text);
+                (!missing.is_empty()).then(|| UnsupportedText {
+                    entity,
+                    id: id.to_owned(),
+                    field,
+                    characters: missing,
+                    suggestion: Font::suggest(&text),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn built_in_fonts() {
+        let ascii = Font::AsciiSmall.characters().unwrap();
+        assert!(ascii.contains('~'));
+        assert!(ascii.contains('\n'));
+        assert_eq!(
+            ascii.missing("Привет, мир"),
+            vec!['П', 'р', 'и', 'в', 'е', 'т', 'м']
+        );
+        assert_eq!(Font::suggest("hello"), Some(Font::AsciiSmall));
+        assert_eq!(Font::suggest("Привет"), Some(Font::UnicodeEuropeanSmall));
+        assert_eq!(Font::suggest("مرحبا"), Some(Font::Arabic));
+        assert_eq!(Font::suggest("こんにちは"), Some(Font::UnicodeAsian));
+        assert_eq!(Font::suggest("Привет こんにちは"), None);
+        assert_eq!(Font::Custom.characters(), None);
+    }
+
+    #[test]
+    fn custom_font() {
+        let data = "FONT tiny\nSIZE 2 2\nCHAR 65\n11\n11\nCHAR 66\n11\n10\nCHAR 1046\n01\n10";
+        let characters = CharacterSet::from_font_data(data);
+        assert_eq!(characters.missing("ABC Ж"), vec!['C', ' ']);
+
+        let mut game = mock::game_default();
+        game.font = Font::Custom;
+        game.custom_font = Some("tiny".to_string());
+        assert_eq!(game.unsupported_text(), vec![]);
+        game.font_data = Some(data.to_string());
+        game.name = "AB".to_string();
+        game.rooms[0].name = None;
+        game.sprites[1].name = None;
+        game.items[0].name = Some("BA".to_string());
+        game.items[1].name = Some("ЖC".to_string());
+        for dialogue in &mut game.dialogues {
+            dialogue.contents = "AB".to_string();
+        }
+        game.dialogues[1].contents = "BAD Ж".to_string();
+
+        let unsupported: Vec<(EntityKind, String, Vec<char>)> = game
+            .unsupported_text()
+            .into_iter()
+            .map(|u| (u.entity, u.id, u.characters))
+            .collect();
+        let expected = vec![
+            (EntityKind::Item, "1".to_string(), vec!['C']),
+            (EntityKind::Dialogue, "1".to_string(), vec!['D', ' ']),
+        ];
+        assert_eq!(unsupported, expected);
+    }
+
+    #[test]
+    fn unsupported_text() {
+        let mut game = mock::game_default();
+        assert_eq!(game.unsupported_text(), vec![]);
+
+        game.dialogues[0].contents = "Я кот".to_string();
+        game.items[0].name = Some("чай".to_string());
+        let unsupported = game.unsupported_text();
+        assert_eq!(
+            unsupported[0],
+            UnsupportedText {
+                entity: EntityKind::Item,
+                id: "0".to_string(),
+                field: "name",
+                characters: vec!['ч', 'а', 'й'],
+                suggestion: Some(Font::UnicodeEuropeanSmall),
+            }
+        );
+        assert_eq!(
+            unsupported[1].to_diagnostic().to_string(),
+            "warning: dialogue 0 contents: the font can't draw 'Я', 'к', 'о', 'т'; try unicode_european_small"
+        );
+
+        game.font = Font::UnicodeEuropeanLarge;
+        assert_eq!(game.unsupported_text(), vec![]);
+    }
+
+    #[test]
+    fn code_is_not_drawn() {
+        let data = "CHAR 65\n1\nCHAR 66\n1";
+        let mut game = mock::game_default();
+        game.font = Font::Custom;
+        game.font_data = Some(data.to_string());
+        game.dialogues[0].contents =
+            "{sequence\n  - AB\n  - {wvy}A{/wvy}\n}{count = count + 1}".to_string();
+
+        let dialogue = |game: &Game| {
+            game.unsupported_text()
+                .into_iter()
+                .find(|u| u.entity == EntityKind::Dialogue && u.id == "0")
+        };
+        assert_eq!(dialogue(&game), None);
+
+        game.dialogues[0].contents = "{say \"hi\"}A{print \"B\"}C{print count}".to_string();
+        assert_eq!(dialogue(&game).unwrap().characters, vec!['h', 'i', 'C']);
+    }
+}
@@ -29,8 +29,10 @@ impl fmt::Display for NotFound {
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Error {
+    Catalog,
     Colour,
     Dialogue,
+    Direction,
     Ending,
     Exit,
     Font,
@@ -38,8 +40,10 @@ pub enum Error {
     Image,
     Item,
     Palette,
+    Playthrough,
     Position,
     Room,
+    Save,
     Sprite,
     Text,
     Tile,
@@ -51,8 +55,10 @@ pub enum Error {
 impl Error {
     pub fn as_str(&self) -> &'static str {
         match self {
+            Error::Catalog => "translation catalog error",
             Error::Colour => "color error",
             Error::Dialogue => "dialog error",
+            Error::Direction => "direction error",
             Error::Ending => "ending error",
             Error::Exit => "exit error",
             Error::Font => "font error",
@@ -60,8 +66,10 @@ impl Error {
             Error::Image => "image error",
             Error::Item => "item error",
             Error::Palette => "palette error",
+            Error::Playthrough => "playthrough error",
             Error::Position => "position error",
             Error::Room => "room error",
+            Error::Save => "save state error",
             Error::Sprite => "sprite error",
             Error::Text => "text error",
             Error::Tile => "tile error",
@@ -39,6 +39,14 @@ impl fmt::Display for RoomFormat {
     }
 }
 
+/// a `! NAME value` line in the header, e.g. `! DLG_COMPAT 0`.
+/// `ROOM_FORMAT` is kept in `Game::room_format` instead.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Flag {
+    pub name: String,
+    pub value: String,
+}
+
 /// in very early versions of Bitsy, a room was called a "set"
 #[derive(Debug, Default, Eq, PartialEq, Copy, Clone)]
 pub enum RoomType {
@@ -57,7 +65,7 @@ impl ToString for RoomType {
     }
 }
 
-#[derive(Debug, Eq, PartialEq, Copy, Clone)]
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Copy, Clone)]
 pub struct Version {
     pub major: u8,
     pub minor: u8,
@@ -86,6 +94,12 @@ impl fmt::Display for VersionError {
 
 impl core::error::Error for VersionError {}
 
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.major, self.minor)
+    }
+}
+
 impl Version {
     pub fn new(str: &str) -> Result<Version, VersionError> {
         let parts: Vec<&str> = str.split('.').collect();
@@ -107,6 +121,8 @@ pub struct Game {
     pub name: String,
     pub version: Option<Version>,
     pub room_format: Option<RoomFormat>,
+    /// header flags other than `ROOM_FORMAT`, added in Bitsy 8
+    pub flags: Vec<Flag>,
     pub(crate) room_type: RoomType,
     pub font: Font,
     pub custom_font: Option<String>, // used if font is Font::Custom
@@ -147,7 +163,15 @@ impl Game {
         match segment {
             Segment::Name(name) => self.name = name,
             Segment::Version(version) => self.version = Some(version),
-            Segment::RoomFormat(room_format) => self.room_format = Some(room_format),
+            Segment::Flags(flags) => {
+                for flag in flags {
+                    if flag.name == "ROOM_FORMAT" {
+                        self.room_format = RoomFormat::from(&flag.value).ok();
+                    } else {
+                        self.flags.push(flag);
+                    }
+                }
+            }
             Segment::Font(font, data) => {
                 self.font = font;
                 if let Some(data) = data {
@@ -193,6 +217,34 @@ impl Game {
         self.get_sprite("A")
     }
 
+    /// in very early versions of Bitsy, sprites had no `DLG` line -
+    /// the sprite's dialogue was the one with the same ID as the sprite.
+    pub fn sprite_dialogue_id<'a>(&self, sprite: &'a Sprite) -> Option<&'a str> {
+        match &sprite.dialogue_id {
+            Some(id) => Some(id),
+            None => self
+                .dialogues
+                .iter()
+                .any(|dialogue| dialogue.id == sprite.id)
+                .then_some(sprite.id.as_str()),
+        }
+    }
+
+    /// whether the tile at the position in the room blocks movement.
+    /// the wall setting of the tile takes precedence over the legacy room wall list.
+    pub fn is_wall(&self, room: &Room, position: Position) -> bool {
+        let Some(tile_id) = room.tile_at(position) else {
+            return false;
+        };
+        if tile_id == "0" {
+            return false;
+        }
+        match self.get_tile(tile_id).and_then(|tile| tile.wall) {
+            Some(wall) => wall,
+            None => room.walls.iter().flatten().any(|id| id == tile_id),
+        }
+    }
+
     pub fn get_room_tiles(&self, room_id: &str) -> Vec<&Tile> {
         let Some(room) = self.get_room(room_id) else {
             return Vec::new();
@@ -263,7 +315,7 @@ impl ToString for Game {
             "{}{}{}{}{}\n\n{}\n\n",
             &self.name,
             &self.version_line(),
-            &self.room_format_line(),
+            &self.flags_line(),
             &self.font_line(),
             &self.text_direction_line(),
             segments.join("\n\n"),
@@ -446,7 +498,7 @@ impl Game {
 
     /// Safely adds a room and returns the room ID (a new ID will be generated if clashing)
     /// You will need to be mindful that the room's palette, tile, exit and ending IDs
-    /// will be valid after adding.
+    /// will be valid after adding. Use `merge()` to import rooms from another game.
     pub fn add_room(&mut self, mut room: Room) -> String {
         let new_id = try_id(&self.room_ids(), &room.id);
         if new_id != room.id {
@@ -465,22 +517,29 @@ impl Game {
     }
 
     fn version_line(&self) -> String {
-        if self.version.is_some() {
-            format!(
-                "\n\n# BITSY VERSION {}.{}",
-                self.version.as_ref().unwrap().major,
-                self.version.as_ref().unwrap().minor
-            )
-        } else {
-            "".to_string()
+        match &self.version {
+            Some(version) => format!("\n\n# BITSY VERSION {}", version),
+            None => "".to_string(),
         }
     }
 
-    fn room_format_line(&self) -> String {
-        if self.room_format.is_some() {
-            format!("\n\n! ROOM_FORMAT {}", self.room_format.unwrap())
-        } else {
+    /// Bitsy 8 writes all flags in one block, with the version first
+    fn flags_line(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        let is_version = |flag: &&Flag| flag.name == "VER_MAJ" || flag.name == "VER_MIN";
+        for flag in self.flags.iter().filter(is_version) {
+            lines.push(format!("! {} {}", flag.name, flag.value));
+        }
+        if let Some(room_format) = self.room_format {
+            lines.push(format!("! ROOM_FORMAT {}", room_format));
+        }
+        for flag in self.flags.iter().filter(|flag| !is_version(flag)) {
+            lines.push(format!("! {} {}", flag.name, flag.value));
+        }
+        if lines.is_empty() {
             "".to_string()
+        } else {
+            format!("\n\n{}", lines.join("\n"))
         }
     }
 
@@ -513,7 +572,7 @@ impl Game {
 /// tries to use an existing ID - if it is already in use, generate a new one
 /// then return the ID (either original or new)
 /// todo refactor (unnecessary clones etc.)
-fn try_id(ids: &[String], id: &str) -> String {
+pub(crate) fn try_id(ids: &[String], id: &str) -> String {
     if is_id_available(ids, id) {
         id.to_string()
     } else {
@@ -526,7 +585,7 @@ fn is_id_available(ids: &[String], id: &str) -> bool {
 }
 
 /// e.g. pass all tile IDs into this to get a new non-conflicting tile ID
-fn new_unique_id(ids: &[String]) -> String {
+pub(crate) fn new_unique_id(ids: &[String]) -> String {
     let mut new_id: u32 = 0;
     while ids.contains(&to_base36(new_id)) {
         new_id += 1;
@@ -534,7 +593,7 @@ fn new_unique_id(ids: &[String]) -> String {
     to_base36(new_id)
 }
 
-fn to_base36(mut x: u32) -> String {
+pub(crate) fn to_base36(mut x: u32) -> String {
     let mut result = Vec::new();
     loop {
         let m = x % 36;
@@ -573,7 +632,18 @@ mod test {
     #[test]
     fn bitsy_v8_14() {
         let given = include_str!["test-resources/default-v8.14.bitsy"];
-        Game::from(given).unwrap();
+        let game = Game::from(given).unwrap();
+        assert_eq!(game.room_format, Some(RoomFormat::CommaSeparated));
+        assert_eq!(
+            game.flags[2],
+            Flag {
+                name: "DLG_COMPAT".to_string(),
+                value: "0".to_string()
+            }
+        );
+        assert!(game.to_string().starts_with(
+            "Write your game's title here\n\n# BITSY VERSION 8.14\n\n! VER_MAJ 8\n! VER_MIN 14\n! ROOM_FORMAT 1\n! DLG_COMPAT 0\n! TXT_MODE 0\n\nPAL 0\n"
+        ));
     }
 
     #[test]
@@ -649,6 +719,22 @@ mod test {
         )
     }
 
+    #[test]
+    fn is_wall() {
+        let mut game = crate::mock::game_default();
+        let room = game.rooms[0].clone();
+        assert!(!game.is_wall(&room, Position { x: 1, y: 1 }));
+        assert!(!game.is_wall(&room, Position { x: 0, y: 0 }));
+
+        game.tiles[0].wall = Some(true);
+        assert!(game.is_wall(&room, Position { x: 1, y: 1 }));
+
+        let mut room = room;
+        game.tiles[0].wall = None;
+        room.walls = Some(vec!["a".to_string()]);
+        assert!(game.is_wall(&room, Position { x: 1, y: 1 }));
+    }
+
     #[test]
     fn add_item() {
         let mut game = crate::mock::game_default();
@@ -669,6 +755,7 @@ mod test {
     fn find_tile_with_animation() {
         let game = crate::mock::game_default();
         let animation = vec![Image {
+            dimension: 8,
             pixels: vec![
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 1,
                 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1,
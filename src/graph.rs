@@ -0,0 +1,381 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::collections::VecDeque;
+use alloc::{string::String, vec::Vec};
+
+/// an exit from one room to another
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomEdge {
+    pub from: String,
+    pub to: String,
+    /// position of the exit in the room it leads from
+    pub position: Position,
+    /// where the avatar appears in the room it leads to
+    pub destination: Position,
+    pub transition: Transition,
+    pub dialogue_id: Option<String>,
+}
+
+/// a placed ending
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomEnding {
+    pub room_id: String,
+    pub ending_id: String,
+    pub position: Position,
+}
+
+/// rooms of a game connected by their exits
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomGraph {
+    pub rooms: Vec<String>,
+    pub edges: Vec<RoomEdge>,
+    pub endings: Vec<RoomEnding>,
+    /// the room the avatar starts in
+    pub start: Option<String>,
+    pub start_position: Option<Position>,
+    /// walkability of each room, if walking is taken into account
+    walk_maps: Vec<WalkMap>,
+}
+
+impl RoomGraph {
+    /// a graph where every exit and ending of a room counts as usable.
+    /// exits leading to rooms that don't exist are left out.
+    pub fn new(game: &Game) -> Self {
+        let rooms = game.room_ids();
+        let mut edges = Vec::new();
+        let mut endings = Vec::new();
+        for room in &game.rooms {
+            for exit in &room.exits {
+                if !rooms.contains(&exit.exit.room_id) {
+                    continue;
+                }
+                edges.push(RoomEdge {
+                    from: room.id.clone(),
+                    to: exit.exit.room_id.clone(),
+                    position: exit.position,
+                    destination: exit.exit.position,
+                    transition: exit.transition.unwrap_or(exit.exit.effect),
+                    dialogue_id: exit.dialogue_id.clone(),
+                });
+            }
+            for ending in &room.endings {
+                endings.push(RoomEnding {
+                    room_id: room.id.clone(),
+                    ending_id: ending.id.clone(),
+                    position: ending.position,
+                });
+            }
+        }
+        let avatar = game.get_avatar();
+        Self {
+            rooms,
+            edges,
+            endings,
+            start: avatar.and_then(|avatar| avatar.room_id.clone()),
+            start_position: avatar.and_then(|avatar| avatar.position),
+            walk_maps: Vec::new(),
+        }
+    }
+
+    /// a graph where exits and endings only count if the avatar can walk to them
+    /// from where it enters the room. this affects `reachable()`, `unreachable()`,
+    /// `reachable_endings()` and `dead_ends()`.
+    pub fn with_walking(game: &Game) -> Self {
+        let mut graph = Self::new(game);
+        graph.walk_maps = game.rooms.iter().map(|r| WalkMap::new(game, r)).collect();
+        graph
+    }
+
+    /// positions where the avatar appears in the room
+    fn entries(&self, room_id: &str) -> Vec<Position> {
+        let mut entries: Vec<Position> = self.edges_to(room_id).map(|e| e.destination).collect();
+        if self.start.as_deref() == Some(room_id) {
+            entries.extend(self.start_position);
+        }
+        entries
+    }
+
+    /// exits and endings of the room that can be used after entering at the position
+    fn usable_from<'a>(
+        &'a self,
+        room_id: &'a str,
+        entry: Position,
+    ) -> (Vec<&'a RoomEdge>, Vec<&'a RoomEnding>) {
+        let walk_map = self.room_index(room_id).and_then(|i| self.walk_maps.get(i));
+        let reachable = walk_map.map(|map| map.reachable_from(entry));
+        let usable = |position: &Position| reachable.as_ref().is_none_or(|r| r.contains(position));
+        let edges = self.edges_from(room_id).filter(|e| usable(&e.position));
+        let endings = self.endings.iter().filter(|e| e.room_id == room_id);
+        let endings = endings.filter(|e| usable(&e.position));
+        (edges.collect(), endings.collect())
+    }
+
+    /// rooms and endings reachable from where the avatar starts
+    fn reach(&self) -> (Vec<String>, Vec<String>) {
+        let mut rooms: Vec<String> = Vec::new();
+        let mut endings: Vec<String> = Vec::new();
+        let (Some(start), Some(position)) = (&self.start, self.start_position) else {
+            return (rooms, endings);
+        };
+        if self.room_index(start).is_none() {
+            return (rooms, endings);
+        }
+        let mut visited = Vec::from([(start.clone(), position)]);
+        let mut queue = VecDeque::from([(start.clone(), position)]);
+        while let Some((room_id, entry)) = queue.pop_front() {
+            if !rooms.contains(&room_id) {
+                rooms.push(room_id.clone());
+            }
+            let (edges, room_endings) = self.usable_from(&room_id, entry);
+            for ending in room_endings {
+                if !endings.contains(&ending.ending_id) {
+                    endings.push(ending.ending_id.clone());
+                }
+            }
+            for edge in edges {
+                let state = (edge.to.clone(), edge.destination);
+                if !visited.contains(&state) {
+                    visited.push(state.clone());
+                    queue.push_back(state);
+                }
+            }
+        }
+        (rooms, endings)
+    }
+
+    pub fn edges_from<'a>(&'a self, room_id: &'a str) -> impl Iterator<Item = &'a RoomEdge> {
+        self.edges.iter().filter(move |edge| edge.from == room_id)
+    }
+
+    pub fn edges_to<'a>(&'a self, room_id: &'a str) -> impl Iterator<Item = &'a RoomEdge> {
+        self.edges.iter().filter(move |edge| edge.to == room_id)
+    }
+
+    /// rooms that can be reached from the room through exits, including the room itself.
+    /// this doesn't take walking into account as the position in the room is unknown.
+    pub fn reachable_from(&self, room_id: &str) -> Vec<String> {
+        if !self.rooms.iter().any(|id| id == room_id) {
+            return Vec::new();
+        }
+        let mut visited = Vec::from([room_id.to_owned()]);
+        let mut queue = VecDeque::from([room_id.to_owned()]);
+        while let Some(current) = queue.pop_front() {
+            for edge in self.edges_from(&current) {
+                if !visited.contains(&edge.to) {
+                    visited.push(edge.to.clone());
+                    queue.push_back(edge.to.clone());
+                }
+            }
+        }
+        visited
+    }
+
+    /// rooms that can be reached from where the avatar starts
+    pub fn reachable(&self) -> Vec<String> {
+        self.reach().0
+    }
+
+    /// rooms that can't be reached from where the avatar starts
+    pub fn unreachable(&self) -> Vec<String> {
+        let reachable = self.reachable();
+        self.rooms
+            .iter()
+            .filter(|id| !reachable.contains(id))
+            .cloned()
+            .collect()
+    }
+
+    /// rooms with no way out: no exits to other rooms and no endings.
+    /// when walking is taken into account, that is from any of the room's entrances.
+    pub fn dead_ends(&self) -> Vec<String> {
+        let has_way_out = |id: &str, entry: Option<Position>| {
+            let (edges, endings) = match entry {
+                Some(entry) => self.usable_from(id, entry),
+                None => (
+                    self.edges_from(id).collect(),
+                    self.endings.iter().filter(|e| e.room_id == id).collect(),
+                ),
+            };
+            !endings.is_empty() || edges.iter().any(|edge| edge.to != id)
+        };
+        self.rooms
+            .iter()
+            .filter(|id| {
+                let entries = self.entries(id);
+                if self.walk_maps.is_empty() || entries.is_empty() {
+                    !has_way_out(id, None)
+                } else {
+                    !entries
+                        .into_iter()
+                        .any(|entry| has_way_out(id, Some(entry)))
+                }
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// endings that can be reached from where the avatar starts
+    pub fn reachable_endings(&self) -> Vec<String> {
+        self.reach().1
+    }
+
+    /// groups of rooms where every room can be reached from any other room of the group
+    pub fn strongly_connected_components(&self) -> Vec<Vec<String>> {
+        let mut tarjan = Tarjan {
+            graph: self,
+            index: 0,
+            indices: Vec::from_iter(self.rooms.iter().map(|_| None)),
+            low_links: Vec::from_iter(self.rooms.iter().map(|_| 0)),
+            stack: Vec::new(),
+            on_stack: Vec::from_iter(self.rooms.iter().map(|_| false)),
+            components: Vec::new(),
+        };
+        for i in 0..self.rooms.len() {
+            if tarjan.indices[i].is_none() {
+                tarjan.visit(i);
+            }
+        }
+        tarjan.components
+    }
+
+    fn room_index(&self, room_id: &str) -> Option<usize> {
+        self.rooms.iter().position(|id| id == room_id)
+    }
+}
+
+/// Tarjan's strongly connected components algorithm
+struct Tarjan<'a> {
+    graph: &'a RoomGraph,
+    index: usize,
+    indices: Vec<Option<usize>>,
+    low_links: Vec<usize>,
+    stack: Vec<usize>,
+    on_stack: Vec<bool>,
+    components: Vec<Vec<String>>,
+}
+
+impl Tarjan<'_> {
+    fn visit(&mut self, v: usize) {
+        self.indices[v] = Some(self.index);
+        self.low_links[v] = self.index;
+        self.index += 1;
+        self.stack.push(v);
+        self.on_stack[v] = true;
+
+        let graph = self.graph;
+        for edge in graph.edges_from(&graph.rooms[v]) {
+            let Some(w) = graph.room_index(&edge.to) else {
+                continue;
+            };
+            match self.indices[w] {
+                None => {
+                    self.visit(w);
+                    self.low_links[v] = self.low_links[v].min(self.low_links[w]);
+                }
+                Some(index) if self.on_stack[w] => {
+                    self.low_links[v] = self.low_links[v].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_links[v]) == self.indices[v] {
+            let mut component = Vec::new();
+            while let Some(w) = self.stack.pop() {
+                self.on_stack[w] = false;
+                component.push(graph.rooms[w].clone());
+                if w == v {
+                    break;
+                }
+            }
+            component.reverse();
+            self.components.push(component);
+        }
+    }
+}
+
+impl Game {
+    pub fn room_graph(&self) -> RoomGraph {
+        RoomGraph::new(self)
+    }
+
+    /// the room graph taking into account where the avatar can walk
+    pub fn walkable_room_graph(&self) -> RoomGraph {
+        RoomGraph::with_walking(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+
+    fn example() -> Game {
+        Game::from(include_str!("test-resources/example.bitsy")).unwrap()
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn edges() {
+        let graph = example().room_graph();
+        let edges: Vec<&RoomEdge> = graph.edges_from("1").collect();
+        assert_eq!(edges.len(), 3);
+        assert_eq!(edges[2].to, "3");
+        assert_eq!(edges[2].position, Position { x: 12, y: 4 });
+        assert_eq!(edges[2].destination, Position { x: 12, y: 6 });
+        assert_eq!(edges[2].transition, Transition::None);
+        assert_eq!(graph.edges_to("4").count(), 1);
+    }
+
+    #[test]
+    fn reachability() {
+        let graph = example().room_graph();
+        assert_eq!(graph.start, Some("0".to_string()));
+        assert_eq!(graph.reachable(), ids(&["0", "1", "2", "3", "4"]));
+        assert_eq!(graph.unreachable(), ids(&["5", "6", "7", "8", "9", "a"]));
+        assert_eq!(graph.reachable_endings(), Vec::<String>::new());
+        assert_eq!(graph.reachable_from("a"), ids(&["a", "3", "4", "2"]));
+    }
+
+    #[test]
+    fn dead_ends() {
+        let mut game = example();
+        game.rooms[3].exits.clear();
+        assert_eq!(game.room_graph().dead_ends(), ids(&["3"]));
+    }
+
+    #[test]
+    fn walking() {
+        let mut game = example();
+        assert_eq!(
+            game.walkable_room_graph().reachable(),
+            game.room_graph().reachable()
+        );
+
+        // move the way from the cellar back to the kitchen behind the wall
+        let cellar = game.rooms.iter_mut().find(|room| room.id == "4").unwrap();
+        cellar.exits[0].position = Position { x: 0, y: 14 };
+        let graph = game.walkable_room_graph();
+        assert_eq!(graph.reachable(), game.room_graph().reachable());
+        assert!(graph.dead_ends().contains(&"4".to_string()));
+        assert!(!game.room_graph().dead_ends().contains(&"4".to_string()));
+
+        // wall off the avatar
+        let start = game.rooms.iter_mut().find(|room| room.id == "0").unwrap();
+        start.tiles[5 * 16 + 3] = "k".to_string();
+        assert_eq!(game.walkable_room_graph().reachable(), ids(&["0"]));
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let graph = example().room_graph();
+        let components = graph.strongly_connected_components();
+        assert!(components.contains(&ids(&["0"])));
+        assert!(components.contains(&ids(&["1"])));
+        assert!(components.contains(&ids(&["2", "3", "4"])));
+        assert_eq!(components.len(), 9);
+    }
+}
@@ -1,57 +1,209 @@
-use alloc::{format, string::String, vec::Vec};
+use crate::Direction;
+use alloc::{string::String, vec, vec::Vec};
 use core::fmt;
 use core::str::FromStr;
 
+/// `pixels` holds `dimension` rows of `dimension` pixels.
+/// an image built with other lengths has no pixels as far as
+/// `get`, `set` and the transforms are concerned, so they leave it as it is.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Image {
+    /// width and height in pixels - 8 for SD, 16 for HD
+    pub dimension: u8,
     pub pixels: Vec<u8>, // 64 for SD, 256 for HD
 }
 
 impl Image {
-    pub fn invert(&mut self) {
-        self.pixels = self.pixels.iter().map(|pixel| 1 - pixel).collect();
+    fn size(&self) -> usize {
+        usize::from(self.dimension)
     }
 
-    /// flip image vertically
-    pub fn flip(&mut self) {
-        let mut pixels = Vec::with_capacity(64);
+    /// whether there are as many pixels as the dimension says
+    fn is_complete(&self) -> bool {
+        self.pixels.len() == self.size() * self.size()
+    }
 
-        for row in self.pixels.chunks(8).rev() {
-            for pixel in row {
-                pixels.push(*pixel);
+    /// builds a new image from the pixel at each position of the old one,
+    /// where `source` maps a new position to an old one
+    fn transform<F>(&mut self, source: F)
+    where
+        F: Fn(usize, usize, usize) -> (usize, usize),
+    {
+        if !self.is_complete() {
+            return;
+        }
+        let size = self.size();
+        let mut pixels = Vec::with_capacity(size * size);
+
+        for y in 0..size {
+            for x in 0..size {
+                let (old_x, old_y) = source(x, y, size - 1);
+                pixels.push(self.pixels[(old_y * size) + old_x]);
             }
         }
 
         self.pixels = pixels;
     }
 
-    /// mirror image horizontally
-    pub fn mirror(&mut self) {
-        let mut pixels = Vec::with_capacity(64);
+    /// a blank SD or HD image
+    pub fn new(dimension: u8) -> Image {
+        Image {
+            dimension,
+            pixels: vec![0; usize::from(dimension).pow(2)],
+        }
+    }
+
+    pub fn width(&self) -> u8 {
+        self.dimension
+    }
+
+    pub fn height(&self) -> u8 {
+        self.dimension
+    }
+
+    fn index(&self, x: u8, y: u8) -> Option<usize> {
+        if x < self.dimension && y < self.dimension && self.is_complete() {
+            Some(usize::from(y) * self.size() + usize::from(x))
+        } else {
+            None
+        }
+    }
+
+    /// the pixel at x,y, or `None` if that is outside the image
+    pub fn get(&self, x: u8, y: u8) -> Option<u8> {
+        self.pixels.get(self.index(x, y)?).copied()
+    }
+
+    /// any value other than 0 sets the pixel to 1.
+    /// positions outside the image are ignored,
+    /// so shapes that don't fit are cut off at the edges
+    pub fn set(&mut self, x: u8, y: u8, value: u8) {
+        if let Some(pixel) = self.index(x, y).and_then(|i| self.pixels.get_mut(i)) {
+            *pixel = u8::from(value != 0);
+        }
+    }
+
+    /// moves every pixel one step, wrapping around at the edges
+    pub fn shift(&mut self, direction: Direction) {
+        match direction {
+            Direction::Up => self.transform(|x, y, last| (x, (y + 1) % (last + 1))),
+            Direction::Down => self.transform(|x, y, last| (x, (y + last) % (last + 1))),
+            Direction::Left => self.transform(|x, y, last| ((x + 1) % (last + 1), y)),
+            Direction::Right => self.transform(|x, y, last| ((x + last) % (last + 1), y)),
+        }
+    }
 
-        for row in self.pixels.chunks(8) {
-            for i in (0..8).rev() {
-                pixels.push(row[i]);
+    /// draws a straight line between two points, including both ends
+    pub fn line(&mut self, from: (u8, u8), to: (u8, u8), value: u8) {
+        let (mut x, mut y) = (i16::from(from.0), i16::from(from.1));
+        let (end_x, end_y) = (i16::from(to.0), i16::from(to.1));
+        let (step_x, step_y) = ((end_x - x).signum(), (end_y - y).signum());
+        let (dx, dy) = ((end_x - x).abs(), -(end_y - y).abs());
+        let mut error = dx + dy;
+
+        // Bresenham's algorithm
+        loop {
+            self.set(x as u8, y as u8, value);
+            if x == end_x && y == end_y {
+                break;
+            }
+            if error * 2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error * 2 <= dx {
+                error += dx;
+                y += step_y;
             }
         }
+    }
 
-        self.pixels = pixels;
+    /// draws the rectangle with corners `from` and `to`, or just its outline
+    pub fn rectangle(&mut self, from: (u8, u8), to: (u8, u8), value: u8, filled: bool) {
+        let (left, right) = (from.0.min(to.0), from.0.max(to.0));
+        let (top, bottom) = (from.1.min(to.1), from.1.max(to.1));
+        for y in top..=bottom {
+            for x in left..=right {
+                let edge = x == left || x == right || y == top || y == bottom;
+                if filled || edge {
+                    self.set(x, y, value);
+                }
+            }
+        }
     }
 
-    /// rotate image 90° clockwise
-    pub fn rotate(&mut self) {
-        let mut pixels = Vec::with_capacity(64);
-
-        // start from bottom-left corner, work upward in that column,
-        // then work on the next column to the right
-        // for counter-clockwise, just reverse x instead of y
-        for x in 0..8 {
-            for y in (0..8).rev() {
-                pixels.push(self.pixels[(y * 8) + x]);
+    /// sets the pixel at x,y and every pixel of the same value
+    /// connected to it up, down, left or right
+    pub fn flood_fill(&mut self, x: u8, y: u8, value: u8) {
+        let Some(target) = self.get(x, y) else {
+            return;
+        };
+        // as with `set`, anything other than 0 is 1
+        if target == u8::from(value != 0) {
+            return;
+        }
+
+        let mut stack = vec![(x, y)];
+        while let Some((x, y)) = stack.pop() {
+            if self.get(x, y) != Some(target) {
+                continue;
+            }
+            self.set(x, y, value);
+            stack.push((x.saturating_add(1), y));
+            stack.push((x, y.saturating_add(1)));
+            if let Some(x) = x.checked_sub(1) {
+                stack.push((x, y));
+            }
+            if let Some(y) = y.checked_sub(1) {
+                stack.push((x, y));
             }
         }
+    }
 
-        self.pixels = pixels;
+    /// copies another image onto this one with its top-left corner at x,y.
+    /// anything that doesn't fit is cut off.
+    pub fn paste(&mut self, other: &Image, x: u8, y: u8) {
+        for other_y in 0..other.height() {
+            for other_x in 0..other.width() {
+                if let Some(value) = other.get(other_x, other_y) {
+                    self.set(x.saturating_add(other_x), y.saturating_add(other_y), value);
+                }
+            }
+        }
+    }
+
+    pub fn invert(&mut self) {
+        self.pixels = self
+            .pixels
+            .iter()
+            .map(|pixel| u8::from(*pixel == 0))
+            .collect();
+    }
+
+    /// flip image vertically
+    pub fn flip(&mut self) {
+        self.transform(|x, y, last| (x, last - y));
+    }
+
+    /// mirror image horizontally
+    pub fn mirror(&mut self) {
+        self.transform(|x, y, last| (last - x, y));
+    }
+
+    /// rotate image 90° clockwise
+    pub fn rotate(&mut self) {
+        // each new row is an old column, read from the bottom up
+        self.transform(|x, y, last| (y, last - x));
+    }
+
+    /// rotate image 90° counter-clockwise
+    pub fn rotate_counter_clockwise(&mut self) {
+        self.transform(|x, y, last| (last - y, x));
+    }
+
+    /// rotate image 180°
+    pub fn rotate_180(&mut self) {
+        self.transform(|x, y, last| (last - x, last - y));
     }
 
     pub fn from_lines<'a, I>(lines: I) -> Result<Image, crate::Error>
@@ -73,10 +225,13 @@ impl Image {
             }
         }
 
-        if pixels.len() != 64 && pixels.len() != 256 {
+        if dimension == 0 || pixels.len() != dimension * dimension {
             return Err(crate::Error::Image);
         }
-        Ok(Image { pixels })
+        Ok(Image {
+            dimension: dimension as u8,
+            pixels,
+        })
     }
 }
 
@@ -91,11 +246,9 @@ impl fmt::Display for Image {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut string = String::new();
 
-        let is_hd = self.pixels.len() >= 16 * 16;
-        let sqrt = if is_hd { 16 } else { 8 };
-        for line in self.pixels.chunks(sqrt) {
+        for line in self.pixels.chunks(self.size().max(1)) {
             for pixel in line {
-                string.push_str(&format!("{}", *pixel));
+                string.push(if *pixel == 0 { '0' } else { '1' });
             }
             string.push('\n');
         }
@@ -118,6 +271,7 @@ mod test {
         let output = Image::from_str(include_str!("test-resources/image")).unwrap();
 
         let expected = Image {
+            dimension: 8,
             pixels: vec![
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
@@ -142,6 +296,7 @@ mod test {
         let output = Image::from_str(include_str!("test-resources/image-oob")).unwrap();
 
         let expected = Image {
+            dimension: 8,
             pixels: vec![
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 1, 1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
@@ -166,6 +321,7 @@ mod test {
         image.flip();
 
         let flipped = Image {
+            dimension: 8,
             pixels: vec![
                 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0,
                 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -182,6 +338,7 @@ mod test {
         image.mirror();
 
         let mirrored = Image {
+            dimension: 8,
             pixels: vec![
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0,
                 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0,
@@ -198,6 +355,7 @@ mod test {
         image.rotate();
 
         let rotated = Image {
+            dimension: 8,
             pixels: vec![
                 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0,
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -207,4 +365,182 @@ mod test {
 
         assert_eq!(image, rotated);
     }
+
+    #[test]
+    fn get_and_set() {
+        let mut image = Image::new(16);
+        assert_eq!((image.width(), image.height()), (16, 16));
+        image.set(3, 2, 1);
+        image.set(16, 0, 1);
+        assert_eq!(image.get(3, 2), Some(1));
+        assert_eq!(image.get(2, 3), Some(0));
+        assert_eq!(image.get(16, 0), None);
+        assert_eq!(image.pixels.iter().filter(|p| **p == 1).count(), 1);
+
+        // pixels are only ever 0 or 1
+        image.set(4, 2, 7);
+        assert_eq!(image.get(4, 2), Some(1));
+        image.pixels[0] = 2;
+        image.invert();
+        assert_eq!(image.get(0, 0), Some(0));
+        assert_eq!(image.get(4, 2), Some(0));
+    }
+
+    #[test]
+    fn shift() {
+        let mut image = Image::new(8);
+        image.set(0, 0, 1);
+        image.shift(Direction::Up);
+        assert_eq!(image.get(0, 7), Some(1));
+        image.shift(Direction::Left);
+        assert_eq!(image.get(7, 7), Some(1));
+        image.shift(Direction::Down);
+        image.shift(Direction::Right);
+        assert_eq!(image, {
+            let mut expected = Image::new(8);
+            expected.set(0, 0, 1);
+            expected
+        });
+    }
+
+    #[test]
+    fn line() {
+        let mut image = Image::new(8);
+        image.line((0, 0), (7, 3), 1);
+        assert_eq!(
+            image.to_string(),
+            "10000000\n01100000\n00011000\n00000111\n00000000\n00000000\n00000000\n00000000"
+        );
+
+        // drawing backwards gives a line between the same points
+        let mut backwards = Image::new(8);
+        backwards.line((7, 3), (0, 0), 1);
+        assert_eq!(backwards.get(0, 0), Some(1));
+        assert_eq!(backwards.get(7, 3), Some(1));
+        assert_eq!(backwards.pixels.iter().filter(|p| **p == 1).count(), 8);
+    }
+
+    #[test]
+    fn rectangle_and_flood_fill() {
+        let mut image = Image::new(8);
+        image.rectangle((6, 5), (1, 1), 1, false);
+        assert_eq!(
+            image.to_string(),
+            "00000000\n01111110\n01000010\n01000010\n01000010\n01111110\n00000000\n00000000"
+        );
+
+        let mut filled = image.clone();
+        filled.flood_fill(3, 3, 1);
+        let mut expected = Image::new(8);
+        expected.rectangle((1, 1), (6, 5), 1, true);
+        assert_eq!(filled, expected);
+
+        // filling with the colour that is already there changes nothing
+        let mut same = image.clone();
+        same.flood_fill(1, 1, 5);
+        assert_eq!(same, image);
+
+        // filling outside the outline leaves the inside alone
+        image.flood_fill(0, 0, 1);
+        assert_eq!(image.get(3, 3), Some(0));
+        assert_eq!(image.pixels.iter().filter(|p| **p == 0).count(), 12);
+    }
+
+    #[test]
+    fn paste() {
+        let mut image = Image::new(16);
+        let chequers = crate::mock::image::chequers_1();
+        image.paste(&chequers, 12, 0);
+        assert_eq!(image.get(12, 0), Some(1));
+        assert_eq!(image.get(13, 0), Some(0));
+        assert_eq!(image.get(15, 7), Some(1));
+        assert_eq!(image.get(11, 0), Some(0));
+        assert_eq!(image.get(12, 8), Some(0));
+        assert_eq!(image.pixels.iter().filter(|p| **p == 1).count(), 16);
+    }
+
+    /// a 16x16 image with a single pixel
+    fn hd_dot(x: u8, y: u8) -> Image {
+        let mut image = Image::new(16);
+        image.set(x, y, 1);
+        image
+    }
+
+    #[test]
+    fn hd_image() {
+        let mut string = String::new();
+        for y in 0..16 {
+            string.push_str(if y == 0 {
+                "0100000000000000"
+            } else {
+                "0000000000000000"
+            });
+            string.push('\n');
+        }
+        let image = Image::from_str(&string).unwrap();
+        assert_eq!(image, hd_dot(1, 0));
+        assert_eq!(image.to_string(), string.trim_end());
+    }
+
+    #[test]
+    fn hd_transforms() {
+        type Transform = fn(&mut Image);
+        let transforms: [(Transform, Image); 5] = [
+            (Image::flip, hd_dot(1, 15)),
+            (Image::mirror, hd_dot(14, 0)),
+            (Image::rotate, hd_dot(15, 1)),
+            (Image::rotate_counter_clockwise, hd_dot(0, 14)),
+            (Image::rotate_180, hd_dot(14, 15)),
+        ];
+        for (transform, expected) in transforms {
+            let mut image = hd_dot(1, 0);
+            transform(&mut image);
+            assert_eq!(image, expected);
+        }
+    }
+
+    #[test]
+    fn inconsistent_dimension() {
+        let original = Image {
+            dimension: 16,
+            pixels: vec![1; 64],
+        };
+        let mut image = original.clone();
+        type Transform = fn(&mut Image);
+        let transforms: [Transform; 5] = [
+            Image::flip,
+            Image::mirror,
+            Image::rotate,
+            Image::rotate_counter_clockwise,
+            |image| image.shift(Direction::Left),
+        ];
+        for transform in transforms {
+            transform(&mut image);
+        }
+        image.set(1, 1, 0);
+        image.flood_fill(0, 0, 0);
+        assert_eq!(image, original);
+        assert_eq!(image.get(0, 0), None);
+
+        let empty = Image {
+            dimension: 0,
+            pixels: vec![],
+        };
+        assert_eq!(empty.to_string(), "");
+    }
+
+    #[test]
+    fn rotations() {
+        let original = crate::mock::image::asymmetrical();
+        let mut image = original.clone();
+        image.rotate();
+        image.rotate_counter_clockwise();
+        assert_eq!(image, original);
+
+        image.rotate();
+        image.rotate();
+        let mut half_turn = original.clone();
+        half_turn.rotate_180();
+        assert_eq!(image, half_turn);
+    }
 }
@@ -0,0 +1,257 @@
+use crate::*;
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// the versions of Bitsy a game could have been made with
+#[derive(Clone, Debug, PartialEq)]
+pub struct VersionEstimate {
+    /// the oldest plausible version
+    pub earliest: Version,
+    /// the first version that is ruled out, if any
+    pub before: Option<Version>,
+    /// from 0 (no evidence at all) to 1 (the version is declared)
+    pub confidence: f32,
+    /// what the estimate is based on, e.g. "rooms are called SET"
+    pub evidence: Vec<String>,
+}
+
+impl VersionEstimate {
+    pub fn contains(&self, version: Version) -> bool {
+        version >= self.earliest && self.before.is_none_or(|before| version < before)
+    }
+}
+
+/// collects bounds, each with a weight for how much it says about the version
+struct Evidence {
+    earliest: Version,
+    before: Option<Version>,
+    /// chance that all of the evidence is misleading
+    doubt: f32,
+    notes: Vec<String>,
+}
+
+impl Evidence {
+    fn at_least(&mut self, version: Version, weight: f32, note: String) {
+        self.earliest = self.earliest.max(version);
+        self.add(weight, note);
+    }
+
+    fn before(&mut self, version: Version, weight: f32, note: String) {
+        self.before = Some(self.before.map_or(version, |before| before.min(version)));
+        self.add(weight, note);
+    }
+
+    fn add(&mut self, weight: f32, note: String) {
+        self.doubt *= 1.0 - weight;
+        self.notes.push(note);
+    }
+}
+
+fn since(feature: Feature) -> Version {
+    feature.since().unwrap_or(Version { major: 1, minor: 0 })
+}
+
+impl Game {
+    /// a guess at the Bitsy version the game was made with, for files without
+    /// a `# BITSY VERSION` line. newer features set the earliest version and
+    /// conventions that were later dropped rule out newer ones.
+    ///
+    /// a declared version is returned as is, with full confidence.
+    pub fn infer_version(&self) -> VersionEstimate {
+        if let Some(version) = self.version {
+            return VersionEstimate {
+                earliest: version,
+                before: Some(Version {
+                    major: version.major,
+                    minor: version.minor.saturating_add(1),
+                }),
+                confidence: 1.0,
+                evidence: vec![format!("declared as {}", version)],
+            };
+        }
+
+        let mut evidence = Evidence {
+            earliest: Version { major: 1, minor: 0 },
+            before: None,
+            doubt: 1.0,
+            notes: Vec::new(),
+        };
+
+        let flag = |name: &str| self.flags.iter().find(|f| f.name == name);
+        if let (Some(major), Some(minor)) = (flag("VER_MAJ"), flag("VER_MIN"))
+            && let (Ok(major), Ok(minor)) = (major.value.parse(), minor.value.parse())
+        {
+            let version = Version { major, minor };
+            evidence.at_least(version, 0.9, format!("header flags say {}", version));
+            evidence.before = Some(Version {
+                major,
+                minor: minor.saturating_add(1),
+            });
+        }
+
+        for feature in self.used_features() {
+            if let Some(version) = feature.since() {
+                let weight = match feature {
+                    Feature::HeaderFlags | Feature::SpriteDialogueIds => 0.3,
+                    _ => 0.2,
+                };
+                evidence.at_least(version, weight, format!("uses {}", feature));
+            }
+        }
+
+        if !self.rooms.is_empty() {
+            let keyword = since(Feature::RoomKeyword);
+            match self.room_type {
+                RoomType::Set => evidence.before(keyword, 0.4, "rooms are called SET".into()),
+                RoomType::Room => evidence.at_least(keyword, 0.1, "rooms are called ROOM".into()),
+            }
+            if self.room_format.is_none() {
+                let note = "no room format flag".into();
+                evidence.before(since(Feature::CommaRoomFormat), 0.2, note);
+            }
+            if self.rooms.iter().any(|room| room.palette_id.is_none()) {
+                evidence.before(keyword, 0.1, "rooms without PAL".into());
+            }
+            if self.rooms.iter().any(|room| room.walls.is_some())
+                && !self.used_features().contains(&Feature::TileWalls)
+            {
+                let note = "walls are listed on rooms".into();
+                evidence.before(since(Feature::TileWalls), 0.1, note);
+            }
+        }
+
+        let implicit_dialogue = self.sprites.iter().any(|sprite| {
+            sprite.dialogue_id.is_none() && self.dialogues.iter().any(|d| d.id == sprite.id)
+        });
+        if implicit_dialogue {
+            let note = "sprite dialogue shares the sprite's ID".into();
+            evidence.before(since(Feature::SpriteDialogueIds), 0.3, note);
+        }
+        // Bitsy 7 numbers dialogue like everything else
+        let numbered = Version { major: 7, minor: 0 };
+        let old_names = self
+            .dialogues
+            .iter()
+            .any(|d| d.id.starts_with("SPR_") || d.id.starts_with("ITM_"));
+        if old_names {
+            evidence.before(numbered, 0.2, "dialogue IDs like SPR_0 and ITM_0".into());
+        } else if self.sprites.iter().any(|s| s.dialogue_id.is_some()) {
+            evidence.at_least(numbered, 0.1, "dialogue IDs are numbered".into());
+        }
+
+        let header_flags = since(Feature::HeaderFlags);
+        if !self.endings.is_empty() {
+            evidence.before(header_flags, 0.2, "endings are END blocks".into());
+        }
+        if self.flags.is_empty() && !self.rooms.is_empty() {
+            evidence.before(header_flags, 0.2, "no header flags".into());
+        }
+
+        let mut confidence = 1.0 - evidence.doubt;
+        // newer features outweigh old conventions, which later versions still read
+        if evidence
+            .before
+            .is_some_and(|before| before <= evidence.earliest)
+        {
+            evidence.before = None;
+            confidence /= 2.0;
+        }
+
+        VersionEstimate {
+            earliest: evidence.earliest,
+            before: evidence.before,
+            confidence,
+            evidence: evidence.notes,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+
+    fn without_version(source: &str) -> Game {
+        let mut game = Game::from(source).unwrap();
+        game.version = None;
+        game
+    }
+
+    #[test]
+    fn declared() {
+        let estimate = mock::game_default().infer_version();
+        assert_eq!(estimate.confidence, 1.0);
+        assert!(estimate.contains(Version {
+            major: 7,
+            minor: 10
+        }));
+        assert!(!estimate.contains(Version {
+            major: 7,
+            minor: 11
+        }));
+    }
+
+    #[test]
+    fn legacy() {
+        let estimate = without_version(include_str!("test-resources/legacy.bitsy")).infer_version();
+        assert_eq!(estimate.earliest, Version { major: 1, minor: 0 });
+        assert_eq!(estimate.before, Some(Version { major: 3, minor: 0 }));
+        assert!(estimate.confidence > 0.5);
+        assert!(estimate.evidence.contains(&"rooms are called SET".into()));
+    }
+
+    #[test]
+    fn bitsy_4() {
+        let estimate =
+            without_version(include_str!("test-resources/example.bitsy")).infer_version();
+        assert_eq!(estimate.earliest, Version { major: 4, minor: 0 });
+        assert_eq!(estimate.before, Some(Version { major: 7, minor: 0 }));
+        assert!(estimate.contains(Version { major: 4, minor: 8 }));
+    }
+
+    #[test]
+    fn bitsy_7_and_8() {
+        let estimate =
+            without_version(include_str!("test-resources/default-v7.10.bitsy")).infer_version();
+        assert_eq!(estimate.earliest, Version { major: 7, minor: 0 });
+        assert_eq!(estimate.before, Some(Version { major: 8, minor: 0 }));
+
+        let estimate =
+            without_version(include_str!("test-resources/default-v8.14.bitsy")).infer_version();
+        assert_eq!(
+            estimate.earliest,
+            Version {
+                major: 8,
+                minor: 14
+            }
+        );
+        assert_eq!(
+            estimate.before,
+            Some(Version {
+                major: 8,
+                minor: 15
+            })
+        );
+        assert!(estimate.confidence > 0.9);
+    }
+
+    #[test]
+    fn conflicting_evidence() {
+        let mut game = without_version(include_str!("test-resources/legacy.bitsy"));
+        game.text_direction = TextDirection::RightToLeft;
+        let estimate = game.infer_version();
+        assert_eq!(estimate.earliest, Version { major: 7, minor: 0 });
+        assert_eq!(estimate.before, None);
+        assert!(estimate.confidence < 0.5);
+    }
+
+    #[test]
+    fn no_evidence() {
+        let estimate = Game::default().infer_version();
+        assert_eq!(estimate.confidence, 0.0);
+        assert!(estimate.contains(Version { major: 1, minor: 0 }));
+        assert!(estimate.contains(Version {
+            major: 8,
+            minor: 14
+        }));
+    }
+}
@@ -6,45 +6,82 @@ use alloc::string::ToString;
 use alloc::{format, string::String, vec::Vec};
 use core::fmt::Display;
 
+mod assets;
+mod capability;
 mod colour;
+mod coverage;
 mod dialogue;
 mod ending;
 mod error;
 mod exit;
 mod frames;
 mod game;
+mod graph;
 mod image;
+mod infer;
 mod instance;
 mod item;
+mod lint;
+mod localization;
+mod merge;
+mod migrate;
 pub mod mock;
 mod palette;
+mod playthrough;
 mod position;
+mod references;
+mod remap;
 mod room;
+mod save;
+mod screenplay;
+mod script;
 mod segments;
+mod solve;
 mod sprite;
+mod state;
 mod test_omnibus;
 mod text;
 mod tile;
+mod validate;
 mod variable;
+mod versioned;
+mod walk;
 
+pub use assets::*;
+pub use capability::*;
 pub use colour::*;
+pub use coverage::*;
 pub use dialogue::*;
 pub use ending::*;
 pub use error::*;
 pub use exit::*;
 pub use frames::*;
 pub use game::*;
+pub use graph::*;
 pub use image::*;
+pub use infer::*;
 pub use instance::*;
 pub use item::*;
+pub use lint::*;
+pub use localization::*;
+pub use merge::*;
 pub use palette::*;
+pub use playthrough::*;
 pub use position::*;
+pub use references::*;
+pub use remap::*;
 pub use room::*;
+pub use save::*;
+pub use script::*;
 pub use segments::*;
+pub use solve::*;
 pub use sprite::*;
+pub use state::*;
 pub use text::*;
 pub use tile::*;
+pub use validate::*;
 pub use variable::*;
+pub use walk::*;
 
 /// e.g. `\nNAME DLG_0`
 fn optional_data_line<T: Display>(label: &str, item: Option<T>) -> String {
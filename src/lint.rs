@@ -0,0 +1,408 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// a check for a common authoring mistake
+pub trait LintRule {
+    /// unique kebab-case name of the rule, used to allow it
+    fn id(&self) -> &'static str;
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>);
+}
+
+/// a diagnostic produced by a lint rule
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Lint {
+    pub rule: &'static str,
+    pub diagnostic: Diagnostic,
+}
+
+pub struct Linter {
+    rules: Vec<Box<dyn LintRule>>,
+    allowed: Vec<String>,
+}
+
+impl Default for Linter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Linter {
+    /// a linter with all the built-in rules
+    pub fn new() -> Self {
+        Self {
+            rules: builtin_rules(),
+            allowed: Vec::new(),
+        }
+    }
+
+    /// a linter without any rules, for running only custom ones
+    pub fn empty() -> Self {
+        Self {
+            rules: Vec::new(),
+            allowed: Vec::new(),
+        }
+    }
+
+    pub fn add_rule(&mut self, rule: Box<dyn LintRule>) {
+        self.rules.push(rule);
+    }
+
+    /// disables the rule with the given ID
+    pub fn allow(&mut self, rule_id: &str) {
+        self.allowed.push(rule_id.to_owned());
+    }
+
+    pub fn rule_ids(&self) -> Vec<&'static str> {
+        self.rules.iter().map(|rule| rule.id()).collect()
+    }
+
+    pub fn run(&self, game: &Game) -> Vec<Lint> {
+        let mut lints = Vec::new();
+        for rule in &self.rules {
+            if self.allowed.iter().any(|id| id == rule.id()) {
+                continue;
+            }
+            let mut diagnostics = Vec::new();
+            rule.check(game, &mut diagnostics);
+            lints.extend(diagnostics.into_iter().map(|diagnostic| Lint {
+                rule: rule.id(),
+                diagnostic,
+            }));
+        }
+        lints
+    }
+}
+
+impl Game {
+    /// runs all built-in lint rules
+    pub fn lint(&self) -> Vec<Lint> {
+        Linter::new().run(self)
+    }
+}
+
+pub fn builtin_rules() -> Vec<Box<dyn LintRule>> {
+    vec![
+        Box::new(ExitOnWall),
+        Box::new(ItemOnWall),
+        Box::new(EndingOnWall),
+        Box::new(AvatarOnWall),
+        Box::new(SpriteOverlap),
+        Box::new(EmptyDialogue),
+        Box::new(RoomWithoutEntrance),
+        Box::new(UnsupportedCharacters),
+    ]
+}
+
+/// an exit placed on a wall can never be used
+pub struct ExitOnWall;
+
+impl LintRule for ExitOnWall {
+    fn id(&self) -> &'static str {
+        "exit-on-wall"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for room in &game.rooms {
+            for exit in &room.exits {
+                if game.is_wall(room, exit.position) {
+                    let message = format!("exit at {} is on a wall", exit.position);
+                    diagnostics.push(Diagnostic::warning(
+                        EntityKind::Room,
+                        &room.id,
+                        "exits",
+                        message,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// an item placed on a wall can never be picked up
+pub struct ItemOnWall;
+
+impl LintRule for ItemOnWall {
+    fn id(&self) -> &'static str {
+        "item-on-wall"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for room in &game.rooms {
+            for item in &room.items {
+                if game.is_wall(room, item.position) {
+                    let message = format!("item {} at {} is on a wall", item.id, item.position);
+                    diagnostics.push(Diagnostic::warning(
+                        EntityKind::Room,
+                        &room.id,
+                        "items",
+                        message,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// an ending placed on a wall can never be reached
+pub struct EndingOnWall;
+
+impl LintRule for EndingOnWall {
+    fn id(&self) -> &'static str {
+        "ending-on-wall"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for room in &game.rooms {
+            for ending in &room.endings {
+                if game.is_wall(room, ending.position) {
+                    let message =
+                        format!("ending {} at {} is on a wall", ending.id, ending.position);
+                    diagnostics.push(Diagnostic::warning(
+                        EntityKind::Room,
+                        &room.id,
+                        "endings",
+                        message,
+                    ));
+                }
+            }
+        }
+    }
+}
+
+pub struct AvatarOnWall;
+
+impl LintRule for AvatarOnWall {
+    fn id(&self) -> &'static str {
+        "avatar-on-wall"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        let Some(avatar) = game.get_avatar() else {
+            return;
+        };
+        let (Some(room_id), Some(position)) = (&avatar.room_id, avatar.position) else {
+            return;
+        };
+        let Some(room) = game.get_room(room_id) else {
+            return;
+        };
+        if game.is_wall(room, position) {
+            let message = format!("avatar starts on a wall at {}", position);
+            diagnostics.push(Diagnostic::warning(
+                EntityKind::Sprite,
+                &avatar.id,
+                "position",
+                message,
+            ));
+        }
+    }
+}
+
+/// only one of the sprites sharing a position can be seen or talked to
+pub struct SpriteOverlap;
+
+impl LintRule for SpriteOverlap {
+    fn id(&self) -> &'static str {
+        "sprite-overlap"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for (i, sprite) in game.sprites.iter().enumerate() {
+            let (Some(room_id), Some(position)) = (&sprite.room_id, sprite.position) else {
+                continue;
+            };
+            let other = game.sprites[..i]
+                .iter()
+                .find(|s| s.room_id.as_ref() == Some(room_id) && s.position == Some(position));
+            if let Some(other) = other {
+                let message = format!(
+                    "sprite shares position {} in room {} with sprite {}",
+                    position, room_id, other.id
+                );
+                diagnostics.push(Diagnostic::warning(
+                    EntityKind::Sprite,
+                    &sprite.id,
+                    "position",
+                    message,
+                ));
+            }
+        }
+    }
+}
+
+pub struct EmptyDialogue;
+
+impl LintRule for EmptyDialogue {
+    fn id(&self) -> &'static str {
+        "empty-dialogue"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for dialogue in &game.dialogues {
+            let contents = dialogue.contents.replace("\"\"\"", "");
+            if contents.trim().is_empty() {
+                let message = "dialogue is empty".to_owned();
+                diagnostics.push(Diagnostic::warning(
+                    EntityKind::Dialogue,
+                    &dialogue.id,
+                    "contents",
+                    message,
+                ));
+            }
+        }
+    }
+}
+
+/// a room that the avatar does not start in and no exit or `{exit}` script leads to
+pub struct RoomWithoutEntrance;
+
+impl LintRule for RoomWithoutEntrance {
+    fn id(&self) -> &'static str {
+        "room-no-entrance"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        let start = game.get_avatar().and_then(|avatar| avatar.room_id.as_ref());
+        let script_exits = game.script_references().exits;
+        for room in &game.rooms {
+            if start == Some(&room.id) {
+                continue;
+            }
+            let has_entrance = game.rooms.iter().any(|other| {
+                other.id != room.id && other.exits.iter().any(|e| e.exit.room_id == room.id)
+            }) || script_exits.iter().any(|exit| exit.room_id == room.id);
+            if !has_entrance {
+                let message = "no exit leads to this room".to_owned();
+                diagnostics.push(Diagnostic::warning(
+                    EntityKind::Room,
+                    &room.id,
+                    "exits",
+                    message,
+                ));
+            }
+        }
+    }
+}
+
+/// text the game's font can't draw shows up as blanks
+pub struct UnsupportedCharacters;
+
+impl LintRule for UnsupportedCharacters {
+    fn id(&self) -> &'static str {
+        "unsupported-characters"
+    }
+
+    fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+        for text in game.unsupported_text() {
+            diagnostics.push(text.to_diagnostic());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+
+    #[test]
+    fn lint_default_game() {
+        assert_eq!(mock::game_default().lint(), vec![]);
+    }
+
+    #[test]
+    fn lint_walls() {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 1, y: 1 },
+            id: "0".to_string(),
+        });
+        game.sprites[0].position = Some(Position { x: 1, y: 2 });
+
+        let rules: Vec<&str> = game.lint().iter().map(|lint| lint.rule).collect();
+        assert_eq!(rules, vec!["item-on-wall", "avatar-on-wall"]);
+    }
+
+    #[test]
+    fn lint_sprite_overlap() {
+        let mut game = mock::game_default();
+        game.sprites[1].position = game.sprites[0].position;
+        let lints = game.lint();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].rule, "sprite-overlap");
+        assert_eq!(lints[0].diagnostic.id, "a");
+    }
+
+    #[test]
+    fn lint_rooms_and_dialogues() {
+        let mut game = mock::game_default();
+        let mut room = mock::room();
+        room.exits[0].exit.room_id = "0".to_string();
+        game.rooms.push(room);
+        game.dialogues[0].contents = "\"\"\"\n\n\"\"\"".to_string();
+
+        let lints = game.lint();
+        let rules: Vec<&str> = lints.iter().map(|lint| lint.rule).collect();
+        assert_eq!(rules, vec!["empty-dialogue", "room-no-entrance"]);
+        assert_eq!(lints[1].diagnostic.id, "a");
+    }
+
+    #[test]
+    fn script_exit_is_an_entrance() {
+        let mut game = mock::game_default();
+        let mut room = mock::room();
+        room.exits.clear();
+        game.rooms.push(room);
+        game.dialogues[0].contents = "down we go{exit \"a\" 1 1}".to_string();
+        assert_eq!(game.lint(), vec![]);
+    }
+
+    #[test]
+    fn allow_rule() {
+        let mut game = mock::game_default();
+        game.sprites[1].position = game.sprites[0].position;
+        let mut linter = Linter::new();
+        assert_eq!(linter.rule_ids(), Linter::default().rule_ids());
+        assert!(Linter::empty().rule_ids().is_empty());
+        linter.allow("sprite-overlap");
+        assert_eq!(linter.run(&game), vec![]);
+    }
+
+    #[test]
+    fn lint_unsupported_characters() {
+        let mut game = mock::game_default();
+        game.name = "Ёлка".to_string();
+        let lints = game.lint();
+        assert_eq!(lints.len(), 1);
+        assert_eq!(lints[0].rule, "unsupported-characters");
+        assert_eq!(lints[0].diagnostic.entity, EntityKind::Game);
+    }
+
+    #[test]
+    fn custom_rule() {
+        struct NoName;
+        impl LintRule for NoName {
+            fn id(&self) -> &'static str {
+                "no-name"
+            }
+
+            fn check(&self, game: &Game, diagnostics: &mut Vec<Diagnostic>) {
+                if game.name.is_empty() {
+                    let message = "game has no title".to_string();
+                    diagnostics.push(Diagnostic::warning(EntityKind::Game, "", "name", message));
+                }
+            }
+        }
+
+        let mut game = mock::game_default();
+        game.name = String::new();
+        let mut linter = Linter::empty();
+        linter.add_rule(Box::new(NoName));
+        assert_eq!(linter.rule_ids(), vec!["no-name"]);
+        assert_eq!(linter.run(&game)[0].rule, "no-name");
+    }
+}
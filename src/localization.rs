@@ -0,0 +1,614 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+
+/// a player-visible string
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Message {
+    /// stable ID made from the entity kind and ID, e.g. `dialogue:SPR_0:1`
+    pub context: String,
+    /// notes for translators, e.g. which sprite says the line
+    pub comments: Vec<String>,
+    /// the original text. inline code like `{wvy}` must be kept in translations.
+    pub source: String,
+    /// empty if not translated yet
+    pub translation: String,
+}
+
+/// a set of messages, read from and written as gettext PO files
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Catalog {
+    /// language code from the PO header, e.g. `ar` or `pt_BR`
+    pub language: Option<String>,
+    pub messages: Vec<Message>,
+}
+
+impl Catalog {
+    pub fn get(&self, context: &str) -> Option<&Message> {
+        self.messages.iter().find(|m| m.context == context)
+    }
+
+    /// a PO template, i.e. the catalog without any translations
+    pub fn to_pot(&self) -> String {
+        let mut template = Catalog {
+            language: None,
+            messages: self.messages.clone(),
+        };
+        for message in &mut template.messages {
+            message.translation.clear();
+        }
+        template.to_string()
+    }
+}
+
+fn escape(string: &str) -> String {
+    let mut escaped = String::new();
+    for c in string.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(string: &str) -> Result<String, crate::Error> {
+    let mut unescaped = String::new();
+    let mut chars = string.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('"') => unescaped.push('"'),
+            Some('\\') => unescaped.push('\\'),
+            _ => return Err(crate::Error::Catalog),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// the contents of a `"..."` string in a PO file
+fn quoted(string: &str) -> Result<String, crate::Error> {
+    let inner = string
+        .trim()
+        .strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .ok_or(crate::Error::Catalog)?;
+    unescape(inner)
+}
+
+impl fmt::Display for Catalog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "msgid \"\"")?;
+        writeln!(f, "msgstr \"\"")?;
+        writeln!(f, "\"Content-Type: text/plain; charset=UTF-8\\n\"")?;
+        if let Some(language) = &self.language {
+            writeln!(f, "\"Language: {}\\n\"", escape(language))?;
+        }
+        for message in &self.messages {
+            writeln!(f)?;
+            for comment in &message.comments {
+                writeln!(f, "#. {}", comment)?;
+            }
+            writeln!(f, "msgctxt \"{}\"", escape(&message.context))?;
+            writeln!(f, "msgid \"{}\"", escape(&message.source))?;
+            writeln!(f, "msgstr \"{}\"", escape(&message.translation))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Catalog {
+    type Err = crate::Error;
+
+    /// reads a PO file. plural forms and obsolete messages are not supported.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut catalog = Catalog::default();
+        let mut message = Message::default();
+        let mut has_id = false;
+        // the field that continuation lines are added to
+        let mut field: Option<&str> = None;
+
+        let mut finish = |message: &mut Message, has_id: &mut bool| {
+            let message = core::mem::take(message);
+            if !*has_id {
+                return;
+            }
+            *has_id = false;
+            if message.source.is_empty() && message.context.is_empty() {
+                // the header
+                for line in message.translation.lines() {
+                    if let Some(language) = line.strip_prefix("Language:") {
+                        let language = language.trim();
+                        if !language.is_empty() {
+                            catalog.language = Some(language.to_owned());
+                        }
+                    }
+                }
+            } else {
+                catalog.messages.push(message);
+            }
+        };
+
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(comment) = line.strip_prefix("#.") {
+                if has_id {
+                    finish(&mut message, &mut has_id);
+                }
+                message.comments.push(comment.trim().to_owned());
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('"') {
+                let value = quoted(line)?;
+                match field {
+                    Some("msgctxt") => message.context.push_str(&value),
+                    Some("msgid") => message.source.push_str(&value),
+                    Some("msgstr") => message.translation.push_str(&value),
+                    _ => return Err(crate::Error::Catalog),
+                }
+                continue;
+            }
+            let (keyword, value) = line.split_once(' ').ok_or(crate::Error::Catalog)?;
+            let value = quoted(value)?;
+            match keyword {
+                "msgctxt" => {
+                    if has_id {
+                        finish(&mut message, &mut has_id);
+                    }
+                    message.context = value;
+                }
+                "msgid" => {
+                    if has_id {
+                        finish(&mut message, &mut has_id);
+                    }
+                    has_id = true;
+                    message.source = value;
+                }
+                "msgstr" if has_id => message.translation = value,
+                _ => return Err(crate::Error::Catalog),
+            }
+            field = Some(match keyword {
+                "msgctxt" => "msgctxt",
+                "msgid" => "msgid",
+                _ => "msgstr",
+            });
+        }
+        finish(&mut message, &mut has_id);
+        Ok(catalog)
+    }
+}
+
+/// a run of text and inline code in a script, without surrounding whitespace
+fn text_spans(source: &str) -> Vec<Span> {
+    let Ok(script) = Script::parse(source) else {
+        // the whole thing is text as far as we can tell
+        return trimmed(
+            source,
+            Span {
+                start: 0,
+                end: source.len(),
+            },
+        )
+        .into_iter()
+        .collect();
+    };
+    let mut spans = Vec::new();
+    collect_spans(source, &script.nodes, &mut spans);
+    spans
+}
+
+fn collect_spans(source: &str, nodes: &[Node], spans: &mut Vec<Span>) {
+    let mut run: Option<Span> = None;
+    let mut has_text = false;
+    for node in nodes {
+        match &node.kind {
+            NodeKind::Sequence {
+                options: branches, ..
+            }
+            | NodeKind::Conditional { branches, .. } => {
+                if let Some(span) = run.take().filter(|_| has_text) {
+                    spans.extend(trimmed(source, span));
+                }
+                has_text = false;
+                for branch in branches {
+                    collect_spans(source, &branch.body, spans);
+                }
+            }
+            kind => {
+                if let NodeKind::Text(text) = kind {
+                    has_text |= !text.trim().is_empty();
+                }
+                run = Some(match run {
+                    Some(span) => Span {
+                        start: span.start,
+                        end: node.span.end,
+                    },
+                    None => node.span,
+                });
+            }
+        }
+    }
+    if let Some(span) = run.filter(|_| has_text) {
+        spans.extend(trimmed(source, span));
+    }
+}
+
+fn trimmed(source: &str, span: Span) -> Option<Span> {
+    let text = &source[span.start..span.end];
+    let start = span.start + (text.len() - text.trim_start().len());
+    let end = span.end - (text.len() - text.trim_end().len());
+    (start < end).then_some(Span { start, end })
+}
+
+/// inline code in a message, e.g. `{wvy}`, sorted so they can be compared.
+/// the code is written with Bitsy's spacing, so spacing differences don't count.
+fn code_tags(text: &str) -> Option<Vec<String>> {
+    let script = Script::parse(text).ok()?;
+    let mut tags = Vec::new();
+    for node in &script.nodes {
+        match &node.kind {
+            NodeKind::Text(_) => {}
+            NodeKind::Sequence { .. } | NodeKind::Conditional { .. } => return None,
+            _ => {
+                let node = Node {
+                    spacing: Vec::new(),
+                    ..node.clone()
+                };
+                tags.push(node.to_string());
+            }
+        }
+    }
+    tags.sort();
+    Some(tags)
+}
+
+/// a translation doesn't keep the inline code of its source
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RejectedTranslation {
+    pub context: String,
+    pub translation: String,
+}
+
+/// the result of applying a catalog to a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct Translation {
+    pub game: Game,
+    /// translations that were left out because their code didn't match the source
+    pub rejected: Vec<RejectedTranslation>,
+}
+
+/// languages written right to left
+const RIGHT_TO_LEFT: [&str; 7] = ["ar", "fa", "he", "ps", "sd", "ur", "yi"];
+
+fn is_right_to_left(c: char) -> bool {
+    matches!(c, '\u{0590}'..='\u{08ff}' | '\u{fb1d}'..='\u{fdff}' | '\u{fe70}'..='\u{feff}')
+}
+
+impl Game {
+    /// all player-visible text: the title, dialogues (including exit dialogues)
+    /// and endings. dialogue is split around sequences and conditionals.
+    pub fn messages(&self) -> Catalog {
+        let mut catalog = Catalog::default();
+        if !self.name.is_empty() {
+            catalog.messages.push(Message {
+                context: "title".to_owned(),
+                source: self.name.clone(),
+                ..Message::default()
+            });
+        }
+
+        for dialogue in &self.dialogues {
+            let mut comments = Vec::new();
+            if let Some(name) = &dialogue.name {
+                comments.push(format!("dialogue {} ({})", dialogue.id, name));
+            }
+            for sprite in &self.sprites {
+                if self.sprite_dialogue_id(sprite) == Some(&dialogue.id) {
+                    comments.push(format!(
+                        "said by sprite {}",
+                        speaker(&sprite.id, &sprite.name)
+                    ));
+                }
+            }
+            for item in &self.items {
+                if item.dialogue_id.as_ref() == Some(&dialogue.id) {
+                    comments.push(format!("shown for item {}", speaker(&item.id, &item.name)));
+                }
+            }
+            for room in &self.rooms {
+                if room
+                    .exits
+                    .iter()
+                    .any(|e| e.dialogue_id.as_ref() == Some(&dialogue.id))
+                {
+                    comments.push(format!("shown on an exit in room {}", room.id));
+                }
+                if room.endings.iter().any(|e| e.id == dialogue.id) {
+                    comments.push(format!("ending in room {}", room.id));
+                }
+            }
+            push_messages(
+                &mut catalog,
+                "dialogue",
+                &dialogue.id,
+                &dialogue.contents,
+                &comments,
+            );
+        }
+
+        for ending in &self.endings {
+            push_messages(&mut catalog, "ending", &ending.id, &ending.dialogue, &[]);
+        }
+
+        catalog
+    }
+
+    /// a copy of the game with the catalog's translations applied.
+    /// untranslated messages are left as they are.
+    ///
+    /// the font is changed to a built-in one if the translation needs characters
+    /// the current font can't draw. once anything is translated, the text direction
+    /// follows the language.
+    pub fn translate(&self, catalog: &Catalog) -> Translation {
+        let mut game = self.clone();
+        let mut rejected = Vec::new();
+        let mut translated_text = String::new();
+
+        let mut translate = |context: String, source: &str| -> Option<String> {
+            let message = catalog.get(&context)?;
+            if message.translation.is_empty() {
+                return None;
+            }
+            if code_tags(source) != code_tags(&message.translation) {
+                rejected.push(RejectedTranslation {
+                    context,
+                    translation: message.translation.clone(),
+                });
+                return None;
+            }
+            translated_text.push_str(&message.translation);
+            Some(message.translation.clone())
+        };
+
+        if let Some(title) = translate("title".to_owned(), &self.name) {
+            game.name = title;
+        }
+        for dialogue in &mut game.dialogues {
+            dialogue.contents =
+                translate_script("dialogue", &dialogue.id, &dialogue.contents, &mut translate);
+        }
+        for ending in &mut game.endings {
+            ending.dialogue =
+                translate_script("ending", &ending.id, &ending.dialogue, &mut translate);
+        }
+
+        // without a language, a catalog may only translate part of the game,
+        // so the direction only changes if the translations need it
+        match &catalog.language {
+            _ if translated_text.is_empty() => {}
+            Some(language) => {
+                let code = language.split(['_', '-']).next().unwrap_or("");
+                game.text_direction = if RIGHT_TO_LEFT.contains(&code) {
+                    TextDirection::RightToLeft
+                } else {
+                    TextDirection::LeftToRight
+                };
+            }
+            None if translated_text.chars().any(is_right_to_left) => {
+                game.text_direction = TextDirection::RightToLeft;
+            }
+            None => {}
+        }
+        let unsupported = game
+            .font_characters()
+            .is_some_and(|characters| !characters.missing(&translated_text).is_empty());
+        if unsupported && let Some(font) = Font::suggest(&translated_text) {
+            game.font = font;
+        }
+
+        Translation { game, rejected }
+    }
+}
+
+fn speaker(id: &str, name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("{} ({})", id, name),
+        None => id.to_owned(),
+    }
+}
+
+fn push_messages(catalog: &mut Catalog, kind: &str, id: &str, source: &str, comments: &[String]) {
+    for (i, span) in text_spans(source).into_iter().enumerate() {
+        catalog.messages.push(Message {
+            context: format!("{}:{}:{}", kind, id, i),
+            comments: comments.to_vec(),
+            source: source[span.start..span.end].to_owned(),
+            translation: String::new(),
+        });
+    }
+}
+
+fn translate_script(
+    kind: &str,
+    id: &str,
+    source: &str,
+    translate: &mut impl FnMut(String, &str) -> Option<String>,
+) -> String {
+    let mut result = source.to_owned();
+    let spans = text_spans(source);
+    // replace from the end so earlier spans stay valid
+    for (i, span) in spans.iter().enumerate().rev() {
+        let original = &source[span.start..span.end];
+        if let Some(translation) = translate(format!("{}:{}:{}", kind, id, i), original) {
+            result.replace_range(span.start..span.end, &translation);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn messages() {
+        let catalog = mock::game_default().messages();
+        let contexts: Vec<&str> = catalog
+            .messages
+            .iter()
+            .map(|m| m.context.as_str())
+            .collect();
+        assert_eq!(
+            contexts,
+            vec!["title", "dialogue:0:0", "dialogue:1:0", "dialogue:2:0"]
+        );
+        let key = catalog.get("dialogue:2:0").unwrap();
+        assert_eq!(key.source, "A key! {wvy}What does it open?{wvy}");
+        assert_eq!(
+            key.comments,
+            vec!["dialogue 2 (key dialog)", "shown for item 1 (key)"]
+        );
+    }
+
+    #[test]
+    fn split_around_blocks() {
+        let game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        let catalog = game.messages();
+        let sources: Vec<&str> = catalog
+            .messages
+            .iter()
+            .filter(|m| m.context.starts_with("dialogue:SPR_0:"))
+            .map(|m| m.source.as_str())
+            .collect();
+        assert_eq!(
+            sources,
+            vec![
+                "here goes nothing.\n                (exit \"cellar 2,8,7\")",
+                "OK. time to gather the jars of sunlight.",
+                "better check the readout first.",
+            ]
+        );
+        // code on its own isn't a message
+        assert!(
+            catalog
+                .messages
+                .iter()
+                .all(|m| m.source != "{jars = jars + 1}")
+        );
+    }
+
+    #[test]
+    fn po_round_trip() {
+        let mut catalog = mock::game_default().messages();
+        catalog.language = Some("fr".to_string());
+        catalog.messages[1].translation = "Je suis un \"chat\"\nmiaou".to_string();
+        let po = catalog.to_string();
+        assert!(po.contains("msgctxt \"dialogue:0:0\"\nmsgid \"I'm a cat\"\nmsgstr \"Je suis un \\\"chat\\\"\\nmiaou\"\n"));
+        assert_eq!(Catalog::from_str(&po), Ok(catalog.clone()));
+
+        let pot = catalog.to_pot();
+        assert!(!pot.contains("Language"));
+        assert!(!pot.contains("miaou"));
+
+        let multi_line = "msgid \"\"\nmsgstr \"\"\n\"Language: de\\n\"\n\nmsgctxt \"title\"\nmsgid \"\"\n\"Write your \"\n\"game's title here\"\nmsgstr \"Titel\"\n";
+        let catalog = Catalog::from_str(multi_line).unwrap();
+        assert_eq!(catalog.language.as_deref(), Some("de"));
+        assert_eq!(catalog.messages[0].source, "Write your game's title here");
+        assert!(Catalog::from_str("msgid hello").is_err());
+    }
+
+    #[test]
+    fn translate() {
+        let game = mock::game_default();
+        let mut catalog = game.messages();
+        catalog.language = Some("ar".to_string());
+        catalog.messages[1].translation = "أنا قطة".to_string();
+        // the effect tags are missing
+        catalog.messages[3].translation = "مفتاح".to_string();
+
+        let translation = game.translate(&catalog);
+        assert_eq!(translation.game.dialogues[0].contents, "أنا قطة");
+        assert_eq!(
+            translation.game.dialogues[2].contents,
+            game.dialogues[2].contents
+        );
+        assert_eq!(
+            translation.rejected,
+            vec![RejectedTranslation {
+                context: "dialogue:2:0".to_string(),
+                translation: "مفتاح".to_string(),
+            }]
+        );
+        assert_eq!(translation.game.font, Font::Arabic);
+        assert_eq!(translation.game.text_direction, TextDirection::RightToLeft);
+
+        // code spaced differently is still the same code
+        catalog.messages[3].translation = "مفتاح! { wvy }ماذا يفتح؟{wvy }".to_string();
+        let translation = game.translate(&catalog);
+        assert!(translation.rejected.is_empty());
+        assert_eq!(
+            translation.game.dialogues[2].contents,
+            "مفتاح! { wvy }ماذا يفتح؟{wvy }"
+        );
+    }
+
+    #[test]
+    fn translate_keeps_layout() {
+        let game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        let mut catalog = game.messages();
+        for message in &mut catalog.messages {
+            if message.source == "better check the readout first." {
+                message.translation = "mieux vaut vérifier l'écran d'abord.".to_string();
+            }
+        }
+        let translation = game.translate(&catalog);
+        let index = game.dialogues.iter().position(|d| d.id == "SPR_0").unwrap();
+        assert_eq!(
+            translation.game.dialogues[index].contents,
+            game.dialogues[index].contents.replace(
+                "better check the readout first.",
+                "mieux vaut vérifier l'écran d'abord."
+            )
+        );
+        assert_eq!(translation.game.font, Font::UnicodeEuropeanSmall);
+        assert_eq!(translation.game.text_direction, TextDirection::LeftToRight);
+    }
+
+    #[test]
+    fn translate_keeps_direction() {
+        let game = Game::from(include_str!("test-resources/arabic.bitsy")).unwrap();
+        assert_eq!(game.text_direction, TextDirection::RightToLeft);
+
+        // an empty catalog without a language changes nothing
+        let catalog = Catalog::default();
+        assert_eq!(game.translate(&catalog).game, game);
+
+        // nor does a catalog with a language but no translations
+        let mut catalog = game.messages();
+        catalog.language = Some("en".to_string());
+        assert_eq!(game.translate(&catalog).game, game);
+
+        catalog.messages[0].translation = "hello".to_string();
+        let translation = game.translate(&catalog);
+        assert_eq!(translation.game.text_direction, TextDirection::LeftToRight);
+    }
+}
@@ -0,0 +1,581 @@
+use crate::*;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MergeOptions {
+    /// import only these entities of the other game - or everything if `None`.
+    /// references to entities that are not imported are left as they are.
+    pub only: Option<AssetIds>,
+    /// reuse existing tiles instead of importing identical copies
+    pub dedupe_tiles: bool,
+    /// reuse existing palettes with the same colours instead of importing copies
+    pub dedupe_palettes: bool,
+}
+
+/// the result of `Game::import_room()`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomImport {
+    /// the ID of the imported room in this game
+    pub room_id: String,
+    /// ID changes applied to the imported room and its dependencies
+    pub ids: IdMap,
+    /// exits of the room that lead to other rooms of the source game and so were not copied
+    pub dropped_exits: Vec<ExitInstance>,
+    /// items, rooms and endings of the source game that the copied scripts refer to,
+    /// but which were not copied. the IDs are those of the source game.
+    pub dangling_references: ScriptReferences,
+}
+
+/// adds the ID to the list unless it is already there
+fn select(ids: &mut Vec<String>, id: &str) {
+    if !ids.iter().any(|i| i == id) {
+        ids.push(id.to_string());
+    }
+}
+
+/// ID changes for a single kind of entity
+struct Allocation {
+    map: BTreeMap<String, String>,
+    /// original IDs of the entities that will be added
+    imported: Vec<String>,
+}
+
+impl Allocation {
+    fn new() -> Self {
+        Self {
+            map: BTreeMap::new(),
+            imported: Vec::new(),
+        }
+    }
+
+    /// the entity will be added using a free ID
+    fn import(&mut self, taken: &mut Vec<String>, id: &str) {
+        let new_id = try_id(taken, id);
+        self.reuse(id, &new_id);
+        taken.push(new_id);
+        self.imported.push(id.to_string());
+    }
+
+    /// references to the entity will point to an existing one instead
+    fn reuse(&mut self, id: &str, existing_id: &str) {
+        if id != existing_id {
+            self.map.insert(id.to_string(), existing_id.to_string());
+        }
+    }
+}
+
+/// adds the remapped entities whose original has one of the given IDs.
+/// the original IDs are checked, as a remapped ID can match an unselected original.
+fn extend<T>(
+    target: &mut Vec<T>,
+    entities: Vec<T>,
+    originals: &[T],
+    ids: &[String],
+    id: fn(&T) -> &String,
+) {
+    target.extend(
+        entities
+            .into_iter()
+            .zip(originals)
+            .filter(|(_, original)| ids.contains(id(original)))
+            .map(|(entity, _)| entity),
+    );
+}
+
+fn is_selected(selection: Option<&Vec<String>>, id: &str) -> bool {
+    selection.is_none_or(|ids| ids.iter().any(|i| i == id))
+}
+
+impl Game {
+    /// imports entities from another game, allocating new IDs where they clash
+    /// and rewriting references inside the imported entities to match.
+    /// the avatar is only imported if this game does not have one.
+    /// returns the ID changes applied to the imported entities.
+    pub fn merge(&mut self, other: &Game, options: MergeOptions) -> IdMap {
+        let only = options.only.as_ref();
+
+        let mut palettes = Allocation::new();
+        let mut taken = self.palette_ids();
+        for palette in &other.palettes {
+            if !is_selected(only.map(|o| &o.palettes), &palette.id) {
+                continue;
+            }
+            let existing = self.palettes.iter().find(|p| p.colours == palette.colours);
+            match existing {
+                Some(existing) if options.dedupe_palettes => {
+                    palettes.reuse(&palette.id, &existing.id)
+                }
+                _ => palettes.import(&mut taken, &palette.id),
+            }
+        }
+
+        let mut tiles = Allocation::new();
+        let mut taken = self.tile_ids();
+        // 0 is reserved for the implicit background tile
+        taken.push("0".to_string());
+        for tile in &other.tiles {
+            if !is_selected(only.map(|o| &o.tiles), &tile.id) {
+                continue;
+            }
+            match self.get_tile_id(tile) {
+                Some(existing) if options.dedupe_tiles => tiles.reuse(&tile.id, &existing),
+                _ => tiles.import(&mut taken, &tile.id),
+            }
+        }
+
+        let mut rooms = Allocation::new();
+        let mut taken = self.room_ids();
+        for room in &other.rooms {
+            if is_selected(only.map(|o| &o.rooms), &room.id) {
+                rooms.import(&mut taken, &room.id);
+            }
+        }
+
+        let mut sprites = Allocation::new();
+        let mut taken = self.sprite_ids();
+        let has_avatar = self.get_avatar().is_some();
+        for sprite in &other.sprites {
+            if sprite.id == "A" && has_avatar {
+                continue;
+            }
+            if is_selected(only.map(|o| &o.sprites), &sprite.id) {
+                sprites.import(&mut taken, &sprite.id);
+            }
+        }
+
+        let mut items = Allocation::new();
+        let mut taken = self.item_ids();
+        for item in &other.items {
+            if is_selected(only.map(|o| &o.items), &item.id) {
+                items.import(&mut taken, &item.id);
+            }
+        }
+
+        let mut dialogues = Allocation::new();
+        let mut taken = self.dialogue_ids();
+        for dialogue in &other.dialogues {
+            if is_selected(only.map(|o| &o.dialogues), &dialogue.id) {
+                dialogues.import(&mut taken, &dialogue.id);
+            }
+        }
+
+        let mut endings = Allocation::new();
+        let mut taken = self.ending_ids();
+        for ending in &other.endings {
+            if is_selected(only.map(|o| &o.endings), &ending.id) {
+                endings.import(&mut taken, &ending.id);
+            }
+        }
+
+        let map = IdMap {
+            palettes: palettes.map,
+            rooms: rooms.map,
+            tiles: tiles.map,
+            sprites: sprites.map,
+            items: items.map,
+            dialogues: dialogues.map,
+            endings: endings.map,
+        };
+
+        let mut imported = other.clone();
+        imported.remap_ids(&map);
+
+        let Game {
+            palettes: new_palettes,
+            rooms: new_rooms,
+            tiles: new_tiles,
+            sprites: new_sprites,
+            items: new_items,
+            dialogues: new_dialogues,
+            endings: new_endings,
+            variables: new_variables,
+            ..
+        } = imported;
+        extend(
+            &mut self.palettes,
+            new_palettes,
+            &other.palettes,
+            &palettes.imported,
+            |p| &p.id,
+        );
+        extend(
+            &mut self.rooms,
+            new_rooms,
+            &other.rooms,
+            &rooms.imported,
+            |r| &r.id,
+        );
+        extend(
+            &mut self.tiles,
+            new_tiles,
+            &other.tiles,
+            &tiles.imported,
+            |t| &t.id,
+        );
+        extend(
+            &mut self.sprites,
+            new_sprites,
+            &other.sprites,
+            &sprites.imported,
+            |s| &s.id,
+        );
+        extend(
+            &mut self.items,
+            new_items,
+            &other.items,
+            &items.imported,
+            |i| &i.id,
+        );
+        extend(
+            &mut self.dialogues,
+            new_dialogues,
+            &other.dialogues,
+            &dialogues.imported,
+            |d| &d.id,
+        );
+        extend(
+            &mut self.endings,
+            new_endings,
+            &other.endings,
+            &endings.imported,
+            |e| &e.id,
+        );
+
+        // variables are global names shared by dialogue scripts, so they are never renamed
+        let variable_ids = self.variable_ids();
+        for variable in new_variables {
+            if is_selected(only.map(|o| &o.variables), &variable.id)
+                && !variable_ids.contains(&variable.id)
+            {
+                self.variables.push(variable);
+            }
+        }
+
+        map
+    }
+
+    /// copies a room from another game along with everything it needs:
+    /// the palette, tiles, placed items, sprites and endings, their dialogues
+    /// and the variables those dialogues use.
+    /// exits leading to other rooms are not copied and are reported instead,
+    /// as are script references to anything else that was not copied.
+    pub fn import_room(
+        &mut self,
+        source: &Game,
+        room_id: &str,
+    ) -> Result<RoomImport, crate::Error> {
+        let Some(mut room) = source.get_room(room_id).cloned() else {
+            return Err(crate::Error::Game {
+                missing: NotFound::Room,
+            });
+        };
+        let (exits, dropped_exits) = room
+            .exits
+            .drain(..)
+            .partition(|exit| exit.exit.room_id == room_id);
+        room.exits = exits;
+        // a room without a palette uses the first one, which may not be first here
+        if room.palette_id.is_none() {
+            room.palette_id = source.palettes.first().map(|palette| palette.id.clone());
+        }
+
+        let mut only = AssetIds::default();
+        select(&mut only.rooms, &room.id);
+        if let Some(id) = &room.palette_id {
+            select(&mut only.palettes, id);
+        }
+        for id in room.tiles.iter().chain(room.walls.iter().flatten()) {
+            if id != "0" {
+                select(&mut only.tiles, id);
+            }
+        }
+        for exit in &room.exits {
+            if let Some(id) = &exit.dialogue_id {
+                select(&mut only.dialogues, id);
+            }
+        }
+        for instance in &room.items {
+            select(&mut only.items, &instance.id);
+            let item = source.get_item(&instance.id);
+            if let Some(id) = item.and_then(|item| item.dialogue_id.as_ref()) {
+                select(&mut only.dialogues, id);
+            }
+        }
+        for instance in &room.endings {
+            select(&mut only.endings, &instance.id);
+            // since Bitsy 8, endings are dialogues
+            select(&mut only.dialogues, &instance.id);
+        }
+        for sprite in &source.sprites {
+            if sprite.id != "A" && sprite.room_id.as_deref() == Some(room_id) {
+                select(&mut only.sprites, &sprite.id);
+                if let Some(id) = source.sprite_dialogue_id(sprite) {
+                    select(&mut only.dialogues, id);
+                }
+            }
+        }
+
+        let mut references = ScriptReferences::default();
+        for dialogue in &source.dialogues {
+            if only.dialogues.contains(&dialogue.id)
+                && let Ok(script) = dialogue.script()
+            {
+                references.script(&script, &dialogue.id);
+            }
+        }
+        for ending in &source.endings {
+            if only.endings.contains(&ending.id)
+                && let Ok(script) = ending.script()
+            {
+                references.script(&script, &ending.id);
+            }
+        }
+        for usage in &references.variables {
+            select(&mut only.variables, &usage.variable);
+        }
+        let dangling_references = ScriptReferences {
+            variables: Vec::new(),
+            // items can be referred to by name as well as by ID
+            items: references
+                .items
+                .into_iter()
+                .filter(|item| {
+                    let id = &item.item_id;
+                    let named = |i: &Item| i.name.as_ref() == Some(id);
+                    !only.items.contains(id)
+                        && !source
+                            .items
+                            .iter()
+                            .any(|i| named(i) && only.items.contains(&i.id))
+                        && !self.items.iter().any(named)
+                })
+                .collect(),
+            exits: references
+                .exits
+                .into_iter()
+                .filter(|exit| exit.room_id != room_id)
+                .collect(),
+            ends: references
+                .ends
+                .into_iter()
+                .filter(|end| {
+                    !only.endings.contains(&end.ending_id)
+                        && !only.dialogues.contains(&end.ending_id)
+                })
+                .collect(),
+        };
+
+        let mut source = source.clone();
+        if let Some(original) = source.rooms.iter_mut().find(|r| r.id == room_id) {
+            *original = room;
+        }
+        let options = MergeOptions {
+            only: Some(only),
+            ..MergeOptions::default()
+        };
+        let ids = self.merge(&source, options);
+        let room_id = ids.rooms.get(room_id).map_or(room_id, |id| id.as_str());
+        Ok(RoomImport {
+            room_id: room_id.to_string(),
+            ids,
+            dropped_exits,
+            dangling_references,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::vec;
+
+    #[test]
+    fn merge_into_itself() {
+        let mut game = mock::game_default();
+        let map = game.merge(&mock::game_default(), MergeOptions::default());
+
+        assert_eq!(game.room_ids(), vec!["0".to_string(), "1".to_string()]);
+        assert_eq!(game.palette_ids(), vec!["0".to_string(), "1".to_string()]);
+        assert_eq!(game.tile_ids(), vec!["a".to_string(), "1".to_string()]);
+        // the avatar is not duplicated
+        assert_eq!(
+            game.sprite_ids(),
+            vec!["A".to_string(), "a".to_string(), "0".to_string()]
+        );
+        assert_eq!(game.items.len(), 4);
+        assert_eq!(game.dialogues.len(), 6);
+        assert_eq!(game.variables.len(), 1);
+        assert_eq!(map.rooms.get("0"), Some(&"1".to_string()));
+
+        let room = game.get_room("1").unwrap();
+        assert_eq!(room.palette_id, Some("1".to_string()));
+        assert!(room.tiles.contains(&"1".to_string()));
+        assert!(!room.tiles.contains(&"a".to_string()));
+
+        let cat = game.get_sprite("0").unwrap();
+        assert_eq!(cat.room_id, Some("1".to_string()));
+        assert_eq!(cat.dialogue_id, Some("3".to_string()));
+        assert_eq!(game.dialogues[3].contents, "I'm a cat");
+    }
+
+    #[test]
+    fn merge_rewrites_scripts() {
+        let mut other = mock::game_default();
+        other.dialogues[2].contents = "A key! {item \"1\"}{exit \"0\" 1 1}".to_string();
+        let mut game = mock::game_default();
+        let map = game.merge(&other, MergeOptions::default());
+
+        assert_eq!(map.items.get("1"), Some(&"3".to_string()));
+        assert_eq!(map.dialogues.get("2"), Some(&"5".to_string()));
+        assert_eq!(
+            game.dialogues[5].contents,
+            "A key! {item \"3\"}{exit \"1\" 1 1}"
+        );
+        assert_eq!(game.dialogues[2], mock::game_default().dialogues[2]);
+    }
+
+    #[test]
+    fn merge_with_dedupe() {
+        let mut game = mock::game_default();
+        let options = MergeOptions {
+            dedupe_tiles: true,
+            dedupe_palettes: true,
+            ..MergeOptions::default()
+        };
+        game.merge(&mock::game_default(), options);
+
+        assert_eq!(game.palette_ids(), vec!["0".to_string()]);
+        assert_eq!(game.tile_ids(), vec!["a".to_string()]);
+        assert_eq!(game.rooms[1].tiles, game.rooms[0].tiles);
+        assert_eq!(game.rooms[1].palette_id, Some("0".to_string()));
+    }
+
+    #[test]
+    fn import_room() {
+        let source = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        let mut game = mock::game_default();
+        let import = game.import_room(&source, "4").unwrap();
+
+        // no clash, so the room keeps its ID
+        assert_eq!(import.room_id, "4");
+        assert_eq!(import.dropped_exits.len(), 1);
+        assert_eq!(import.dropped_exits[0].exit.room_id, "3");
+
+        let room = game.get_room("4").unwrap();
+        assert!(room.exits.is_empty());
+        assert_eq!(room.palette_id, Some("3".to_string()));
+        assert!(game.get_palette("3").is_some());
+        for id in &room.tiles {
+            assert!(id == "0" || game.get_tile(id).is_some());
+        }
+        for instance in &room.items {
+            assert!(game.get_item(&instance.id).is_some());
+        }
+
+        // the two sprites placed in the cellar, with their dialogues
+        assert_eq!(game.sprites.len(), 4);
+        for sprite in &game.sprites[2..] {
+            assert_eq!(sprite.room_id, Some("4".to_string()));
+            let dialogue_id = sprite.dialogue_id.as_ref().unwrap();
+            assert!(game.dialogues.iter().any(|d| &d.id == dialogue_id));
+        }
+    }
+
+    #[test]
+    fn import_room_scripts() {
+        let mut source = mock::game_default();
+        source.variables.push(Variable {
+            id: "x".to_string(),
+            initial_value: VariableValue::Boolean(false),
+        });
+        source.dialogues[0].contents =
+            "{x = x + 1}{item \"0\"}{item \"tea\"}{exit \"9\" 1 1}{exit \"0\" 2 2}{end \"z\"}"
+                .to_string();
+        let mut game = mock::game_default();
+        game.items.clear();
+        let import = game.import_room(&source, "0").unwrap();
+
+        assert_eq!(game.variable_ids(), vec!["a".to_string(), "x".to_string()]);
+        let dangling = import.dangling_references;
+        let items: Vec<&str> = dangling.items.iter().map(|i| i.item_id.as_str()).collect();
+        assert_eq!(items, vec!["0", "tea"]);
+        assert_eq!(dangling.items[0].dialogue_id, "0");
+        assert_eq!(dangling.exits.len(), 1);
+        assert_eq!(dangling.exits[0].room_id, "9");
+        assert_eq!(dangling.ends.len(), 1);
+        assert_eq!(dangling.ends[0].ending_id, "z");
+    }
+
+    #[test]
+    fn import_room_default_palette() {
+        let mut source = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        source
+            .rooms
+            .iter_mut()
+            .find(|r| r.id == "4")
+            .unwrap()
+            .palette_id = None;
+        let mut game = mock::game_default();
+        let import = game.import_room(&source, "4").unwrap();
+
+        let room = game.get_room(&import.room_id).unwrap();
+        let palette_id = room.palette_id.as_ref().unwrap();
+        assert_ne!(palette_id, &game.palettes[0].id);
+        assert_eq!(
+            game.get_palette(palette_id).unwrap().colours,
+            source.palettes[0].colours
+        );
+    }
+
+    #[test]
+    fn import_missing_room() {
+        let mut game = mock::game_default();
+        assert!(game.import_room(&mock::game_default(), "9").is_err());
+    }
+
+    #[test]
+    fn merge_selected() {
+        let mut game = mock::game_default();
+        let options = MergeOptions {
+            only: Some(AssetIds {
+                dialogues: vec!["2".to_string()],
+                items: vec!["1".to_string()],
+                ..AssetIds::default()
+            }),
+            ..MergeOptions::default()
+        };
+        game.merge(&mock::game_default(), options);
+
+        assert_eq!(game.rooms.len(), 1);
+        assert_eq!(game.dialogues.len(), 4);
+        let key = game.items.last().unwrap();
+        assert_eq!(key.id, "2");
+        assert_eq!(key.dialogue_id, Some("3".to_string()));
+    }
+
+    #[test]
+    fn merge_selected_without_collisions() {
+        // the imported copy of tile a is renamed to 1, which the unselected tile 1 also has
+        let mut other = mock::game_default();
+        let mut tile = mock::tile_default();
+        tile.id = "1".to_string();
+        tile.name = Some("unselected".to_string());
+        other.tiles.push(tile);
+
+        let mut game = mock::game_default();
+        let options = MergeOptions {
+            only: Some(AssetIds {
+                tiles: vec!["a".to_string()],
+                ..AssetIds::default()
+            }),
+            ..MergeOptions::default()
+        };
+        let map = game.merge(&other, options);
+
+        assert_eq!(map.tiles.get("a"), Some(&"1".to_string()));
+        assert_eq!(game.tile_ids(), vec!["a".to_string(), "1".to_string()]);
+        assert_eq!(game.tiles[1].name, mock::tile_default().name);
+    }
+}
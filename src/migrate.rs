@@ -0,0 +1,234 @@
+use crate::*;
+use alloc::{string::String, vec::Vec};
+
+impl Game {
+    /// upgrades the game to the format of a newer Bitsy version.
+    /// fails without changing anything if the game is newer than `version`.
+    ///
+    /// the steps are applied oldest first, each only if `version` needs it:
+    /// 1. `SET` rooms become `ROOM`s
+    /// 2. rooms are written with comma-separated tile IDs
+    /// 3. room wall lists become `WAL true` on tiles
+    /// 4. sprites get an explicit `DLG` for the dialogue sharing their ID
+    /// 5. the Bitsy 8 header flags are added
+    pub fn migrate_to(&mut self, version: Version) -> Result<(), crate::Error> {
+        if self.version() > version {
+            return Err(crate::Error::Version);
+        }
+        if version.supports(Feature::RoomKeyword) {
+            self.rename_sets();
+        }
+        if version.supports(Feature::CommaRoomFormat) {
+            self.room_format = Some(RoomFormat::CommaSeparated);
+        }
+        if version.supports(Feature::TileWalls) {
+            self.move_walls_to_tiles();
+        }
+        if version.supports(Feature::SpriteDialogueIds) {
+            self.add_sprite_dialogue_ids();
+        }
+        if version.supports(Feature::HeaderFlags) {
+            self.add_header_flags(version);
+        }
+        self.version = Some(version);
+        Ok(())
+    }
+
+    /// only the keyword changes, rooms are otherwise the same
+    pub(crate) fn rename_sets(&mut self) {
+        self.room_type = RoomType::Room;
+    }
+
+    /// a tile in a room's wall list becomes a wall tile. if the tile is also
+    /// used in rooms that don't list it, those rooms keep the original and the
+    /// listing rooms get a copy with `WAL true`. tiles that already have a wall
+    /// setting are left alone, since it took precedence over the room list.
+    pub(crate) fn move_walls_to_tiles(&mut self) {
+        let lists: Vec<(usize, Vec<String>)> = self
+            .rooms
+            .iter()
+            .enumerate()
+            .filter_map(|(i, room)| Some((i, room.walls.clone()?)))
+            .collect();
+
+        let mut copies: Vec<(String, String)> = Vec::new();
+        for (_, walls) in &lists {
+            for id in walls {
+                let Some(tile) = self.tiles.iter().find(|t| &t.id == id) else {
+                    continue;
+                };
+                if tile.wall.is_some() || copies.iter().any(|(original, _)| original == id) {
+                    continue;
+                }
+                let used_elsewhere = self.rooms.iter().any(|room| {
+                    room.tiles.contains(id)
+                        && !room.walls.as_ref().is_some_and(|walls| walls.contains(id))
+                });
+                if used_elsewhere {
+                    let mut copy = tile.clone();
+                    copy.id = self.new_tile_id();
+                    copy.wall = Some(true);
+                    copies.push((id.clone(), copy.id.clone()));
+                    self.tiles.push(copy);
+                } else if let Some(tile) = self.tiles.iter_mut().find(|t| &t.id == id) {
+                    tile.wall = Some(true);
+                }
+            }
+        }
+
+        for (i, walls) in lists {
+            let room = &mut self.rooms[i];
+            for tile_id in &mut room.tiles {
+                if !walls.contains(tile_id) {
+                    continue;
+                }
+                if let Some((_, copy)) = copies.iter().find(|(original, _)| original == tile_id) {
+                    *tile_id = copy.clone();
+                }
+            }
+            room.walls = None;
+        }
+    }
+
+    /// before `DLG` on sprites, a sprite said the dialogue with its own ID
+    pub(crate) fn add_sprite_dialogue_ids(&mut self) {
+        let ids: Vec<Option<String>> = self
+            .sprites
+            .iter()
+            .map(|sprite| self.sprite_dialogue_id(sprite).map(String::from))
+            .collect();
+        for (sprite, id) in self.sprites.iter_mut().zip(ids) {
+            sprite.dialogue_id = id;
+        }
+    }
+
+    /// the version flags follow `version`. `DLG_COMPAT 0` and `TXT_MODE 0`
+    /// are Bitsy 8's defaults and are only added if missing.
+    pub(crate) fn add_header_flags(&mut self, version: Version) {
+        let defaults = [
+            ("VER_MAJ", version.major.to_string()),
+            ("VER_MIN", version.minor.to_string()),
+            ("DLG_COMPAT", "0".to_string()),
+            ("TXT_MODE", "0".to_string()),
+        ];
+        for (name, value) in defaults {
+            match self.flags.iter_mut().find(|flag| flag.name == name) {
+                Some(flag) if name.starts_with("VER_") => flag.value = value,
+                Some(_) => {}
+                None => self.flags.push(Flag {
+                    name: name.to_string(),
+                    value,
+                }),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    const V8: Version = Version {
+        major: 8,
+        minor: 14,
+    };
+
+    fn legacy() -> Game {
+        Game::from(include_str!("test-resources/legacy.bitsy")).unwrap()
+    }
+
+    #[test]
+    fn rename_sets() {
+        let mut game = legacy();
+        assert!(game.to_string().contains("\nSET 0\n"));
+        game.migrate_to(Version { major: 3, minor: 0 }).unwrap();
+        let output = game.to_string();
+        assert!(output.contains("\nROOM 0\n0000000000000000\n"));
+        assert!(output.contains("\nWAL a\n"));
+    }
+
+    #[test]
+    fn comma_room_format_and_walls() {
+        let mut game = legacy();
+        game.migrate_to(Version { major: 4, minor: 0 }).unwrap();
+        assert_eq!(game.room_format, Some(RoomFormat::CommaSeparated));
+        assert!(
+            game.to_string()
+                .contains("\nROOM 1\n0,0,0,0,0,0,0,0,0,0,0,0,0,0,0,0\n")
+        );
+
+        // tile a is also used in room 1, where it isn't a wall
+        assert_eq!(game.rooms[0].walls, None);
+        assert_eq!(game.tiles[0].wall, None);
+        assert_eq!(game.tiles[1].id, "1");
+        assert_eq!(game.tiles[1].wall, Some(true));
+        assert_eq!(game.rooms[0].tile_at(Position { x: 1, y: 1 }), Some("1"));
+        assert_eq!(game.rooms[1].tile_at(Position { x: 1, y: 1 }), Some("a"));
+
+        let original = legacy();
+        for room_index in 0..2 {
+            for y in 0..16 {
+                for x in 0..16 {
+                    let position = Position { x, y };
+                    assert_eq!(
+                        game.is_wall(&game.rooms[room_index], position),
+                        original.is_wall(&original.rooms[room_index], position)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn walls_without_copies() {
+        let mut game = mock::game_default();
+        game.rooms[0].walls = Some(vec!["a".to_string()]);
+        game.migrate_to(V8).unwrap();
+        assert_eq!(game.tiles.len(), 1);
+        assert_eq!(game.tiles[0].wall, Some(true));
+    }
+
+    #[test]
+    fn sprite_dialogue_ids() {
+        let mut game = legacy();
+        game.migrate_to(Version { major: 7, minor: 0 }).unwrap();
+        assert_eq!(game.sprites[0].dialogue_id, None);
+        assert_eq!(game.sprites[1].dialogue_id, Some("a".to_string()));
+        assert!(game.to_string().contains("\nDLG a\n"));
+    }
+
+    #[test]
+    fn header_flags() {
+        let mut game = legacy();
+        game.migrate_to(V8).unwrap();
+        assert!(game.to_string().starts_with(
+            "old game\n\n# BITSY VERSION 8.14\n\n! VER_MAJ 8\n! VER_MIN 14\n! ROOM_FORMAT 1\n! DLG_COMPAT 0\n! TXT_MODE 0\n\nPAL 0\n"
+        ));
+
+        // an already migrated game stays the same
+        let mut default = Game::from(include_str!("test-resources/default-v8.14.bitsy")).unwrap();
+        let before = default.clone();
+        default.migrate_to(V8).unwrap();
+        assert_eq!(default, before);
+    }
+
+    #[test]
+    fn migrate_example() {
+        let mut game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        game.migrate_to(V8).unwrap();
+        let game = Game::from(&game.to_string()).unwrap();
+        assert_eq!(game.version, Some(V8));
+        assert!(game.sprites.iter().skip(1).all(|s| s.dialogue_id.is_some()));
+    }
+
+    #[test]
+    fn no_downgrade() {
+        let mut game = mock::game_default();
+        let version = Version { major: 6, minor: 5 };
+        assert_eq!(game.migrate_to(version), Err(crate::Error::Version));
+        assert_eq!(game, mock::game_default());
+    }
+}
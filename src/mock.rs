@@ -9,6 +9,7 @@ pub mod image {
 
     pub fn chequers_1() -> Image {
         Image {
+            dimension: 8,
             pixels: vec![
                 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1,
                 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0,
@@ -19,6 +20,7 @@ pub mod image {
 
     pub fn chequers_2() -> Image {
         Image {
+            dimension: 8,
             pixels: vec![
                 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0,
                 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 1,
@@ -29,6 +31,7 @@ pub mod image {
 
     pub fn asymmetrical() -> Image {
         Image {
+            dimension: 8,
             pixels: vec![
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0,
                 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0,
@@ -40,6 +43,7 @@ pub mod image {
     pub fn animation_frames() -> Vec<Image> {
         vec![
             Image {
+                dimension: 8,
                 pixels: vec![
                     1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1,
                     0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0,
@@ -47,6 +51,7 @@ pub mod image {
                 ],
             },
             Image {
+                dimension: 8,
                 pixels: vec![
                     1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 1,
                     1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1,
@@ -54,6 +59,7 @@ pub mod image {
                 ],
             },
             Image {
+                dimension: 8,
                 pixels: vec![
                     0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0,
                     1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0, 0, 1,
@@ -61,6 +67,7 @@ pub mod image {
                 ],
             },
             Image {
+                dimension: 8,
                 pixels: vec![
                     0, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 0,
                     0, 1, 1, 0, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0, 1, 1, 0, 1, 1, 0, 0,
@@ -76,6 +83,7 @@ pub fn avatar() -> Sprite {
         id: "0".to_string(),
         animation_frames: vec![
             Image {
+                dimension: 8,
                 pixels: vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 1,
                     1, 0, 1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
@@ -83,6 +91,7 @@ pub fn avatar() -> Sprite {
                 ],
             },
             Image {
+                dimension: 8,
                 pixels: vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 1,
                     1, 1, 1, 1, 1, 0, 1, 1, 1, 0, 1, 1, 1, 0, 1, 0, 0, 1, 1, 0, 0, 1, 1, 1, 1, 1,
@@ -105,6 +114,7 @@ pub fn tile_default() -> Tile {
         name: Some("block".to_string()),
         wall: None,
         animation_frames: vec![Image {
+            dimension: 8,
             pixels: vec![
                 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 1,
                 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1,
@@ -121,6 +131,7 @@ pub fn tile_background() -> Tile {
         name: None,
         wall: None,
         animation_frames: vec![Image {
+            dimension: 8,
             pixels: vec![0; 64],
         }],
         colour_id: None,
@@ -132,6 +143,7 @@ pub fn sprite() -> Sprite {
         id: "a".to_string(),
         name: Some("hatch".to_string()),
         animation_frames: vec![Image {
+            dimension: 8,
             pixels: vec![
                 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0, 0, 1, 1,
                 1, 1, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 1, 1, 1, 1, 0, 0, 1, 0, 1, 1, 1, 1, 0,
@@ -155,6 +167,7 @@ pub mod item {
         Item {
             id: "1".to_string(),
             animation_frames: vec![Image {
+                dimension: 8,
                 pixels: vec![
                     0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0,
                     1, 1, 1, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1,
@@ -172,6 +185,7 @@ pub fn item() -> Item {
     Item {
         id: "6".to_string(),
         animation_frames: vec![Image {
+            dimension: 8,
             pixels: vec![
                 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                 0, 1, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
@@ -495,6 +509,7 @@ pub fn game_default() -> Game {
             minor: 10,
         }),
         room_format: Some(RoomFormat::CommaSeparated),
+        flags: Vec::new(),
         room_type: RoomType::Room,
         font: Font::AsciiSmall,
         custom_font: None,
@@ -793,6 +808,7 @@ pub fn game_default() -> Game {
             Sprite {
                 id: "A".to_string(),
                 animation_frames: vec![Image {
+                    dimension: 8,
                     pixels: vec![
                         0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0, 0, 0, 1, 1, 0, 0, 0, 0,
                         0, 1, 1, 1, 1, 0, 0, 0, 1, 1, 1, 1, 1, 1, 0, 1, 0, 1, 1, 1, 1, 0, 1, 0, 0,
@@ -810,6 +826,7 @@ pub fn game_default() -> Game {
                 id: "a".to_string(),
                 name: Some("cat".to_string()),
                 animation_frames: vec![Image {
+                    dimension: 8,
                     pixels: vec![
                         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 1, 0, 0, 0, 1, 0,
                         1, 1, 1, 0, 0, 0, 1, 0, 1, 1, 1, 0, 0, 1, 0, 0, 1, 1, 1, 1, 1, 0, 0, 0, 0,
@@ -827,6 +844,7 @@ pub fn game_default() -> Game {
             Item {
                 id: "0".to_string(),
                 animation_frames: vec![Image {
+                    dimension: 8,
                     pixels: vec![
                         0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
                         0, 1, 1, 1, 1, 0, 0, 0, 1, 1, 0, 0, 1, 0, 0, 0, 0, 1, 0, 0, 1, 0, 0, 0, 0,
@@ -859,7 +877,10 @@ pub fn game_default() -> Game {
         endings: Vec::new(),
         variables: vec![Variable {
             id: "a".to_string(),
-            initial_value: "42".to_string(),
+            initial_value: VariableValue::Number {
+                value: 42.0,
+                text: "42".to_string(),
+            },
         }],
         font_data: None,
     }
@@ -0,0 +1,365 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+
+/// something that should be true at a point in a playthrough
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expectation {
+    Room(String),
+    Position(Position),
+    /// the avatar carries at least this many of the item
+    Item {
+        id: String,
+        count: u32,
+    },
+    Ending(String),
+    /// the dialogue was shown during the previous move
+    Dialogue(String),
+}
+
+impl FromStr for Expectation {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split_whitespace();
+        let kind = parts.next().ok_or(crate::Error::Playthrough)?;
+        let value = parts.next().ok_or(crate::Error::Playthrough)?;
+        let count = parts.next();
+        if parts.next().is_some() || (count.is_some() && kind != "item") {
+            return Err(crate::Error::Playthrough);
+        }
+        match kind {
+            "room" => Ok(Expectation::Room(value.to_owned())),
+            "position" if value.contains(',') => Ok(Expectation::Position(value.parse()?)),
+            "item" => Ok(Expectation::Item {
+                id: value.to_owned(),
+                count: match count {
+                    Some(count) => count.parse().map_err(|_| crate::Error::Playthrough)?,
+                    None => 1,
+                },
+            }),
+            "ending" => Ok(Expectation::Ending(value.to_owned())),
+            "dialogue" => Ok(Expectation::Dialogue(value.to_owned())),
+            _ => Err(crate::Error::Playthrough),
+        }
+    }
+}
+
+impl fmt::Display for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expectation::Room(id) => write!(f, "room {}", id),
+            Expectation::Position(position) => write!(f, "position {}", position),
+            Expectation::Item { id, count: 1 } => write!(f, "item {}", id),
+            Expectation::Item { id, count } => write!(f, "item {} {}", id, count),
+            Expectation::Ending(id) => write!(f, "ending {}", id),
+            Expectation::Dialogue(id) => write!(f, "dialogue {}", id),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PlayStep {
+    Move { direction: Direction, times: u32 },
+    Expect(Expectation),
+}
+
+impl FromStr for PlayStep {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(expectation) = s.strip_prefix("expect ") {
+            return Ok(PlayStep::Expect(expectation.parse()?));
+        }
+        let (direction, times) = match s.split_once(' ') {
+            Some((direction, times)) => {
+                let times = times
+                    .trim()
+                    .strip_prefix('x')
+                    .ok_or(crate::Error::Playthrough)?;
+                let times = times.parse().map_err(|_| crate::Error::Playthrough)?;
+                (direction, times)
+            }
+            None => (s, 1),
+        };
+        Ok(PlayStep::Move {
+            direction: direction.parse()?,
+            times,
+        })
+    }
+}
+
+impl fmt::Display for PlayStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlayStep::Move {
+                direction,
+                times: 1,
+            } => write!(f, "{}", direction),
+            PlayStep::Move { direction, times } => write!(f, "{} x{}", direction, times),
+            PlayStep::Expect(expectation) => write!(f, "expect {}", expectation),
+        }
+    }
+}
+
+/// an expectation that didn't hold
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlaythroughFailure {
+    /// index of the failing step
+    pub step: usize,
+    /// `None` if the game couldn't be started
+    pub expectation: Option<Expectation>,
+    /// what was found instead
+    pub actual: String,
+}
+
+impl fmt::Display for PlaythroughFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.expectation {
+            Some(expectation) => write!(
+                f,
+                "step {}: expected {}, found {}",
+                self.step + 1,
+                expectation,
+                self.actual
+            ),
+            None => write!(f, "could not start the game: {}", self.actual),
+        }
+    }
+}
+
+/// a list of moves and expectations to check a game against.
+///
+/// the text format has one step per line (or separated by `, `):
+///
+/// ```text
+/// # comments and blank lines are ignored
+/// right x3
+/// up
+/// expect room 2
+/// expect position 4,5
+/// expect item 1
+/// expect ending a
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Playthrough {
+    pub steps: Vec<PlayStep>,
+}
+
+impl Playthrough {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// a playthrough following the moves, e.g. from a `Solution`
+    pub fn from_moves(moves: &[Direction]) -> Self {
+        let mut playthrough = Self::new();
+        for direction in moves {
+            playthrough.push_move(*direction);
+        }
+        playthrough
+    }
+
+    /// adds a move, merging it with the previous one if it's in the same direction
+    pub fn push_move(&mut self, direction: Direction) {
+        if let Some(PlayStep::Move {
+            direction: previous,
+            times,
+        }) = self.steps.last_mut()
+            && *previous == direction
+        {
+            *times += 1;
+            return;
+        }
+        self.steps.push(PlayStep::Move {
+            direction,
+            times: 1,
+        });
+    }
+
+    pub fn push_expectation(&mut self, expectation: Expectation) {
+        self.steps.push(PlayStep::Expect(expectation));
+    }
+
+    /// records the state's room and position, and its ending if the game is over
+    pub fn checkpoint(&mut self, state: &GameState) {
+        self.push_expectation(Expectation::Room(state.room_id().to_owned()));
+        self.push_expectation(Expectation::Position(state.position()));
+        if let Some(id) = state.ending_id() {
+            self.push_expectation(Expectation::Ending(id.to_owned()));
+        }
+    }
+
+    /// plays the game from the start, stopping at the first failed expectation
+    pub fn run(&self, game: &Game) -> Result<(), PlaythroughFailure> {
+        let mut state = GameState::new(game).map_err(|error| PlaythroughFailure {
+            step: 0,
+            expectation: None,
+            actual: error.to_string(),
+        })?;
+        let mut events: Vec<Event> = Vec::new();
+
+        for (step, play_step) in self.steps.iter().enumerate() {
+            let expectation = match play_step {
+                PlayStep::Move { direction, times } => {
+                    events.clear();
+                    for _ in 0..*times {
+                        events.extend(state.step(*direction));
+                    }
+                    continue;
+                }
+                PlayStep::Expect(expectation) => expectation,
+            };
+            let actual = match expectation {
+                Expectation::Room(id) if state.room_id() != id => {
+                    Some(format!("room {}", state.room_id()))
+                }
+                Expectation::Position(position) if state.position() != *position => {
+                    Some(format!("position {}", state.position()))
+                }
+                Expectation::Item { id, count } if state.item_count(id) < *count => {
+                    Some(format!("{} of item {}", state.item_count(id), id))
+                }
+                Expectation::Ending(id) if state.ending_id() != Some(id) => {
+                    Some(match state.ending_id() {
+                        Some(ending_id) => format!("ending {}", ending_id),
+                        None => "no ending".to_owned(),
+                    })
+                }
+                Expectation::Dialogue(id) => {
+                    let shown = events.iter().any(|event| {
+                        matches!(event, Event::ShowDialogue { dialogue_id } if dialogue_id == id)
+                    });
+                    (!shown).then(|| "no such dialogue".to_owned())
+                }
+                _ => None,
+            };
+            if let Some(actual) = actual {
+                return Err(PlaythroughFailure {
+                    step,
+                    expectation: Some(expectation.clone()),
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Playthrough {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut steps = Vec::new();
+        for line in s.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            for step in line.split(", ") {
+                steps.push(step.parse()?);
+            }
+        }
+        Ok(Self { steps })
+    }
+}
+
+impl fmt::Display for Playthrough {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "{}", step)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 7, y: 3 },
+            id: "0".to_string(),
+        });
+        game.rooms[0].endings.push(Instance {
+            position: Position { x: 7, y: 2 },
+            id: "0".to_string(),
+        });
+        game
+    }
+
+    #[test]
+    fn playthrough_from_str() {
+        let playthrough = Playthrough::from_str(
+            "# get the tea\nright x3, up\nexpect room 0\n\nexpect item 0 2\nexpect position 7,3",
+        )
+        .unwrap();
+        let expected = vec![
+            PlayStep::Move {
+                direction: Direction::Right,
+                times: 3,
+            },
+            PlayStep::Move {
+                direction: Direction::Up,
+                times: 1,
+            },
+            PlayStep::Expect(Expectation::Room("0".to_string())),
+            PlayStep::Expect(Expectation::Item {
+                id: "0".to_string(),
+                count: 2,
+            }),
+            PlayStep::Expect(Expectation::Position(Position { x: 7, y: 3 })),
+        ];
+        assert_eq!(playthrough.steps, expected);
+        assert_eq!(
+            playthrough.to_string(),
+            "right x3\nup\nexpect room 0\nexpect item 0 2\nexpect position 7,3\n"
+        );
+        assert!(Playthrough::from_str("jump").is_err());
+        assert!(Playthrough::from_str("expect position 7").is_err());
+    }
+
+    #[test]
+    fn run() {
+        let game = game();
+        let playthrough = Playthrough::from_str(
+            "right x3, up\nexpect item 0\nexpect dialogue 1\nup\nexpect ending 0",
+        )
+        .unwrap();
+        assert_eq!(playthrough.run(&game), Ok(()));
+
+        let playthrough = Playthrough::from_str("right x3, up, expect item 0 2").unwrap();
+        let failure = playthrough.run(&game).unwrap_err();
+        assert_eq!(failure.step, 2);
+        assert_eq!(
+            failure.to_string(),
+            "step 3: expected item 0 2, found 1 of item 0"
+        );
+    }
+
+    #[test]
+    fn record_solution() {
+        let game = game();
+        let moves = game.solve().moves_to("0").unwrap().to_vec();
+        let mut playthrough = Playthrough::from_moves(&moves);
+        let mut state = game.play().unwrap();
+        for direction in moves {
+            state.step(direction);
+        }
+        playthrough.checkpoint(&state);
+        assert_eq!(
+            playthrough.to_string(),
+            "up x2\nright x3\nexpect room 0\nexpect position 7,2\nexpect ending 0\n"
+        );
+        assert_eq!(playthrough.run(&game), Ok(()));
+    }
+}
@@ -7,6 +7,82 @@ pub struct Position {
     pub y: u8,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+}
+
+impl FromStr for Direction {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(Direction::Up),
+            "down" => Ok(Direction::Down),
+            "left" => Ok(Direction::Left),
+            "right" => Ok(Direction::Right),
+            _ => Err(crate::Error::Direction),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Direction::Up => "up",
+                Direction::Down => "down",
+                Direction::Left => "left",
+                Direction::Right => "right",
+            }
+        )
+    }
+}
+
+impl Position {
+    /// rooms are 16x16 tiles
+    pub fn in_bounds(&self) -> bool {
+        self.x < 16 && self.y < 16
+    }
+
+    /// the neighbouring position in the direction, if it is inside the room
+    pub fn step(&self, direction: Direction) -> Option<Position> {
+        let position = match direction {
+            Direction::Up => Position {
+                x: self.x,
+                y: self.y.checked_sub(1)?,
+            },
+            Direction::Down => Position {
+                x: self.x,
+                y: self.y.checked_add(1)?,
+            },
+            Direction::Left => Position {
+                x: self.x.checked_sub(1)?,
+                y: self.y,
+            },
+            Direction::Right => Position {
+                x: self.x.checked_add(1)?,
+                y: self.y,
+            },
+        };
+        position.in_bounds().then_some(position)
+    }
+}
+
 impl FromStr for Position {
     type Err = crate::Error;
 
@@ -48,6 +124,31 @@ mod test {
         assert!(Position::from_str("14,-1").is_err())
     }
 
+    #[test]
+    fn position_step() {
+        let position = Position { x: 0, y: 15 };
+        assert_eq!(position.step(Direction::Up), Some(Position { x: 0, y: 14 }));
+        assert_eq!(
+            position.step(Direction::Right),
+            Some(Position { x: 1, y: 15 })
+        );
+        assert_eq!(position.step(Direction::Down), None);
+        assert_eq!(position.step(Direction::Left), None);
+
+        // positions outside the room can be parsed, but can't be stepped from
+        let position = Position { x: 255, y: 255 };
+        assert_eq!(position.step(Direction::Down), None);
+        assert_eq!(position.step(Direction::Right), None);
+    }
+
+    #[test]
+    fn direction_from_str() {
+        for direction in Direction::ALL {
+            assert_eq!(Direction::from_str(&direction.to_string()), Ok(direction));
+        }
+        assert!(Direction::from_str("north").is_err());
+    }
+
     #[test]
     fn position_to_string() {
         assert_eq!(Position { x: 4, y: 12 }.to_string(), "4,12".to_string())
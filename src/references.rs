@@ -0,0 +1,332 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec::Vec};
+
+/// where a variable is used in dialogue scripts
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VariableUsage {
+    pub variable: String,
+    /// whether the variable is in `Game::variables`
+    pub declared: bool,
+    /// IDs of the dialogues and endings that read the variable
+    pub readers: Vec<String>,
+    /// IDs of the dialogues and endings that assign to the variable
+    pub writers: Vec<String>,
+}
+
+/// an item referenced with `{item "id"}` in a dialogue script
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemReference {
+    pub dialogue_id: String,
+    pub item_id: String,
+}
+
+/// a room entered with `{exit "id" x y}` in a dialogue script
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ExitReference {
+    pub dialogue_id: String,
+    pub room_id: String,
+}
+
+/// an ending (or, since Bitsy 8, a dialogue) shown with `{end "id"}` in a dialogue script
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndReference {
+    pub dialogue_id: String,
+    pub ending_id: String,
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ScriptReferences {
+    /// declared variables first, then undeclared ones in order of use
+    pub variables: Vec<VariableUsage>,
+    pub items: Vec<ItemReference>,
+    pub exits: Vec<ExitReference>,
+    pub ends: Vec<EndReference>,
+}
+
+impl ScriptReferences {
+    pub fn variable(&self, id: &str) -> Option<&VariableUsage> {
+        self.variables.iter().find(|usage| usage.variable == id)
+    }
+
+    fn usage(&mut self, id: &str) -> &mut VariableUsage {
+        let index = match self.variables.iter().position(|u| u.variable == id) {
+            Some(index) => index,
+            None => {
+                self.variables.push(VariableUsage {
+                    variable: id.to_owned(),
+                    ..VariableUsage::default()
+                });
+                self.variables.len() - 1
+            }
+        };
+        &mut self.variables[index]
+    }
+
+    fn read(&mut self, variable: &str, dialogue_id: &str) {
+        let readers = &mut self.usage(variable).readers;
+        if !readers.iter().any(|id| id == dialogue_id) {
+            readers.push(dialogue_id.to_owned());
+        }
+    }
+
+    fn write(&mut self, variable: &str, dialogue_id: &str) {
+        let writers = &mut self.usage(variable).writers;
+        if !writers.iter().any(|id| id == dialogue_id) {
+            writers.push(dialogue_id.to_owned());
+        }
+    }
+
+    fn function(&mut self, name: &str, arguments: &[Expression], dialogue_id: &str) {
+        let id = match arguments.first() {
+            Some(Expression::String(id) | Expression::Number(id)) => Some(id.clone()),
+            _ => None,
+        };
+        let dialogue = dialogue_id.to_owned();
+        match (name, id) {
+            ("item", Some(item_id)) => self.items.push(ItemReference {
+                dialogue_id: dialogue,
+                item_id,
+            }),
+            ("exit", Some(room_id)) => self.exits.push(ExitReference {
+                dialogue_id: dialogue,
+                room_id,
+            }),
+            ("end", Some(ending_id)) => self.ends.push(EndReference {
+                dialogue_id: dialogue,
+                ending_id,
+            }),
+            // the first argument is the name of a property, not a variable
+            ("property", _) => {
+                for argument in arguments.iter().skip(1) {
+                    self.expression(argument, dialogue_id);
+                }
+                return;
+            }
+            _ => {}
+        }
+        for argument in arguments {
+            self.expression(argument, dialogue_id);
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression, dialogue_id: &str) {
+        match expression {
+            Expression::Variable(variable) => self.read(variable, dialogue_id),
+            Expression::Call { name, arguments } => self.function(name, arguments, dialogue_id),
+            Expression::Binary { left, right, .. } => {
+                self.expression(left, dialogue_id);
+                self.expression(right, dialogue_id);
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn script(&mut self, script: &Script, dialogue_id: &str) {
+        script.visit(&mut |node| match &node.kind {
+            NodeKind::Function { name, arguments } => {
+                self.function(name, arguments, dialogue_id);
+            }
+            NodeKind::Assignment { variable, value } => {
+                self.expression(value, dialogue_id);
+                self.write(variable, dialogue_id);
+            }
+            NodeKind::Expression(expression) => self.expression(expression, dialogue_id),
+            NodeKind::Conditional { branches, .. } => {
+                for condition in branches.iter().filter_map(|b| b.condition.as_ref()) {
+                    self.expression(condition, dialogue_id);
+                }
+            }
+            NodeKind::Text(_) | NodeKind::Sequence { .. } => {}
+        });
+    }
+}
+
+impl Game {
+    /// variables, items, exits and endings used in dialogue and ending scripts.
+    /// scripts with syntax errors are skipped.
+    pub fn script_references(&self) -> ScriptReferences {
+        let mut references = ScriptReferences::default();
+        for variable in &self.variables {
+            references.usage(&variable.id).declared = true;
+        }
+        for dialogue in &self.dialogues {
+            if let Ok(script) = dialogue.script() {
+                references.script(&script, &dialogue.id);
+            }
+        }
+        for ending in &self.endings {
+            if let Ok(script) = ending.script() {
+                references.script(&script, &ending.id);
+            }
+        }
+        references
+    }
+
+    /// syntax errors, undeclared variables, variables that are written but
+    /// never read, and references to items that don't exist
+    pub fn validate_scripts(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let scripts = self
+            .dialogues
+            .iter()
+            .map(|d| (EntityKind::Dialogue, &d.id, "contents", d.script()))
+            .chain(
+                self.endings
+                    .iter()
+                    .map(|e| (EntityKind::Ending, &e.id, "dialogue", e.script())),
+            );
+        let mut undeclared = Vec::new();
+        let mut missing_items = Vec::new();
+        for (entity, id, field, script) in scripts {
+            let script = match script {
+                Ok(script) => script,
+                Err(error) => {
+                    diagnostics.push(Diagnostic {
+                        severity: Severity::Error,
+                        entity,
+                        id: id.clone(),
+                        field,
+                        message: format!("syntax error: {}", error),
+                    });
+                    continue;
+                }
+            };
+            let mut references = ScriptReferences::default();
+            references.script(&script, id);
+            for usage in &references.variables {
+                if !self.variables.iter().any(|v| v.id == usage.variable) {
+                    undeclared.push(Diagnostic {
+                        severity: Severity::Warning,
+                        entity,
+                        id: id.clone(),
+                        field,
+                        message: format!("variable {} is not declared", usage.variable),
+                    });
+                }
+            }
+            for reference in &references.items {
+                // items can be referred to by name as well as by ID
+                let found = self.get_item(&reference.item_id).is_some()
+                    || self
+                        .items
+                        .iter()
+                        .any(|item| item.name.as_ref() == Some(&reference.item_id));
+                if !found {
+                    missing_items.push(Diagnostic {
+                        severity: Severity::Error,
+                        entity,
+                        id: id.clone(),
+                        field,
+                        message: format!("item {} not found", reference.item_id),
+                    });
+                }
+            }
+        }
+        diagnostics.append(&mut undeclared);
+
+        for usage in &self.script_references().variables {
+            if !usage.writers.is_empty() && usage.readers.is_empty() {
+                diagnostics.push(Diagnostic {
+                    severity: Severity::Warning,
+                    entity: EntityKind::Variable,
+                    id: usage.variable.clone(),
+                    field: "id",
+                    message: "variable is written but never read".to_owned(),
+                });
+            }
+        }
+        diagnostics.append(&mut missing_items);
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn example_references() {
+        let game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        let references = game.script_references();
+        let jars = references.variable("jars").unwrap();
+        assert!(!jars.declared);
+        assert_eq!(jars.readers, vec!["ITM_6".to_string(), "SPR_0".to_string()]);
+        assert_eq!(jars.writers, vec!["ITM_6".to_string()]);
+        let checked = references.variable("checked").unwrap();
+        assert_eq!(checked.readers, vec!["SPR_0".to_string()]);
+        assert_eq!(checked.writers, vec!["SPR_1".to_string()]);
+    }
+
+    #[test]
+    fn exit_and_end_references() {
+        let mut game = mock::game_default();
+        game.dialogues[0].contents = "{exit \"1\" 2 3}{end 4}".to_string();
+        let references = game.script_references();
+        assert_eq!(
+            references.exits,
+            vec![ExitReference {
+                dialogue_id: "0".to_string(),
+                room_id: "1".to_string(),
+            }]
+        );
+        assert_eq!(
+            references.ends,
+            vec![EndReference {
+                dialogue_id: "0".to_string(),
+                ending_id: "4".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn validate_default_game_scripts() {
+        assert_eq!(mock::game_default().validate_scripts(), vec![]);
+    }
+
+    #[test]
+    fn validate_scripts() {
+        let mut game = mock::game_default();
+        game.dialogues[0].contents =
+            "{b = a + c}{property locked true}{\n  - {item \"9\"} > 0 ? hi\n}".to_string();
+        game.dialogues[1].contents = "{oops".to_string();
+
+        let output: Vec<String> = game
+            .validate_scripts()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        let expected = vec![
+            "error: dialogue 1 contents: syntax error: code block is not closed at 0..5",
+            "warning: dialogue 0 contents: variable c is not declared",
+            "warning: dialogue 0 contents: variable b is not declared",
+            "warning: variable b id: variable is written but never read",
+            "error: dialogue 0 contents: item 9 not found",
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn validate_ending_scripts() {
+        let mut game = mock::game_default();
+        game.dialogues[0].contents = "{\n  - {item \"key\"} > 0 ? hi\n}".to_string();
+        game.endings.push(Ending {
+            id: "0".to_string(),
+            dialogue: "{d + 1} of {item \"pen\"}".to_string(),
+        });
+
+        let output: Vec<String> = game
+            .validate_scripts()
+            .iter()
+            .map(|d| d.to_string())
+            .collect();
+        let expected = vec![
+            "warning: ending 0 dialogue: variable d is not declared",
+            "error: ending 0 dialogue: item pen not found",
+        ];
+        assert_eq!(output, expected);
+    }
+}
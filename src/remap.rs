@@ -0,0 +1,555 @@
+use crate::*;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+
+/// old ID -> new ID replacements for each kind of game entity.
+/// variables are not included as their IDs are names used in dialogue scripts.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct IdMap {
+    pub palettes: BTreeMap<String, String>,
+    pub rooms: BTreeMap<String, String>,
+    pub tiles: BTreeMap<String, String>,
+    pub sprites: BTreeMap<String, String>,
+    pub items: BTreeMap<String, String>,
+    pub dialogues: BTreeMap<String, String>,
+    pub endings: BTreeMap<String, String>,
+}
+
+impl IdMap {
+    pub fn is_empty(&self) -> bool {
+        self.palettes.is_empty()
+            && self.rooms.is_empty()
+            && self.tiles.is_empty()
+            && self.sprites.is_empty()
+            && self.items.is_empty()
+            && self.dialogues.is_empty()
+            && self.endings.is_empty()
+    }
+}
+
+/// maps IDs to sequential base-36 IDs in their current order.
+/// reserved IDs are left as they are and never assigned to anything else.
+/// entities sharing an ID (which is invalid but happens) keep sharing the new one.
+fn dense_ids(ids: &[String], reserved: &[&str]) -> BTreeMap<String, String> {
+    let mut map = BTreeMap::new();
+    let mut seen: Vec<&str> = Vec::new();
+    let mut next: u32 = 0;
+    for id in ids {
+        if reserved.contains(&id.as_str()) || seen.contains(&id.as_str()) {
+            continue;
+        }
+        seen.push(id);
+        let mut new_id = to_base36(next);
+        while reserved.contains(&new_id.as_str()) {
+            next += 1;
+            new_id = to_base36(next);
+        }
+        next += 1;
+        if *id != new_id {
+            map.insert(id.clone(), new_id);
+        }
+    }
+    map
+}
+
+fn remap(ids: &BTreeMap<String, String>, id: &mut String) {
+    if let Some(new_id) = ids.get(id.as_str()) {
+        *id = new_id.clone();
+    }
+}
+
+fn remap_option(ids: &BTreeMap<String, String>, id: &mut Option<String>) {
+    if let Some(id) = id {
+        remap(ids, id);
+    }
+}
+
+/// rewrites the IDs passed to `{item}`, `{exit}` and `{end}` in a script
+struct ScriptRemap<'a> {
+    map: &'a IdMap,
+    ending_ids: &'a [String],
+    changed: bool,
+}
+
+impl ScriptRemap<'_> {
+    fn nodes(&mut self, nodes: &mut [Node]) {
+        for node in nodes {
+            match &mut node.kind {
+                NodeKind::Function { name, arguments } => self.call(name, arguments),
+                NodeKind::Assignment { value, .. } => self.expression(value),
+                NodeKind::Expression(expression) => self.expression(expression),
+                NodeKind::Sequence { options, .. } => {
+                    for option in options {
+                        self.nodes(&mut option.body);
+                    }
+                }
+                NodeKind::Conditional { branches, .. } => {
+                    for branch in branches {
+                        if let Some(condition) = &mut branch.condition {
+                            self.expression(condition);
+                        }
+                        self.nodes(&mut branch.body);
+                    }
+                }
+                NodeKind::Text(_) => {}
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Call { name, arguments } => self.call(name, arguments),
+            Expression::Binary { left, right, .. } => {
+                self.expression(left);
+                self.expression(right);
+            }
+            _ => {}
+        }
+    }
+
+    fn call(&mut self, name: &str, arguments: &mut [Expression]) {
+        if let Some(first) = arguments.first_mut()
+            && let Expression::String(id) | Expression::Number(id) = first
+        {
+            let ids = match name {
+                "item" => Some(&self.map.items),
+                "exit" => Some(&self.map.rooms),
+                // since Bitsy 8, endings are dialogues
+                "end" if self.ending_ids.contains(id) => Some(&self.map.endings),
+                "end" => Some(&self.map.dialogues),
+                _ => None,
+            };
+            if let Some(new_id) = ids.and_then(|ids| ids.get(id.as_str())) {
+                let numeric = !new_id.is_empty() && new_id.chars().all(|c| c.is_ascii_digit());
+                *first = match first {
+                    Expression::Number(_) if numeric => Expression::Number(new_id.clone()),
+                    _ => Expression::String(new_id.clone()),
+                };
+                self.changed = true;
+            }
+        }
+        for argument in arguments {
+            self.expression(argument);
+        }
+    }
+}
+
+/// scripts with syntax errors are left as they are.
+fn remap_script(source: &mut String, map: &IdMap, ending_ids: &[String]) {
+    if !source.contains('{') {
+        return;
+    }
+    let Ok(mut script) = Script::parse(source) else {
+        return;
+    };
+    let mut remap = ScriptRemap {
+        map,
+        ending_ids,
+        changed: false,
+    };
+    remap.nodes(&mut script.nodes);
+    if remap.changed {
+        *source = script.to_string();
+    }
+}
+
+impl Game {
+    /// renames entities and rewrites every reference to them according to the map,
+    /// including item, room and ending IDs in dialogue and ending scripts.
+    /// all replacements happen at once, so IDs can be swapped.
+    /// scripts with syntax errors can't be rewritten; `validate_scripts` reports them.
+    pub fn remap_ids(&mut self, map: &IdMap) {
+        // a legacy implicit sprite dialogue would be lost if either ID changes
+        for sprite in &mut self.sprites {
+            if sprite.dialogue_id.is_none()
+                && self.dialogues.iter().any(|d| d.id == sprite.id)
+                && (map.sprites.contains_key(&sprite.id) || map.dialogues.contains_key(&sprite.id))
+            {
+                sprite.dialogue_id = Some(sprite.id.clone());
+            }
+        }
+
+        let ending_ids = self.ending_ids();
+
+        for palette in &mut self.palettes {
+            remap(&map.palettes, &mut palette.id);
+        }
+
+        for room in &mut self.rooms {
+            remap(&map.rooms, &mut room.id);
+            remap_option(&map.palettes, &mut room.palette_id);
+            for id in &mut room.tiles {
+                remap(&map.tiles, id);
+            }
+            for id in room.walls.iter_mut().flatten() {
+                remap(&map.tiles, id);
+            }
+            for item in &mut room.items {
+                remap(&map.items, &mut item.id);
+            }
+            for exit in &mut room.exits {
+                remap(&map.rooms, &mut exit.exit.room_id);
+                remap_option(&map.dialogues, &mut exit.dialogue_id);
+            }
+            for ending in &mut room.endings {
+                // since Bitsy 8, endings are dialogues
+                if ending_ids.contains(&ending.id) {
+                    remap(&map.endings, &mut ending.id);
+                } else {
+                    remap(&map.dialogues, &mut ending.id);
+                }
+            }
+        }
+
+        for tile in &mut self.tiles {
+            remap(&map.tiles, &mut tile.id);
+        }
+
+        for sprite in &mut self.sprites {
+            remap(&map.sprites, &mut sprite.id);
+            remap_option(&map.rooms, &mut sprite.room_id);
+            remap_option(&map.dialogues, &mut sprite.dialogue_id);
+            // inventory lines are `ITM <item ID> <count>`
+            for line in &mut sprite.items {
+                let (id, count) = line.split_once(' ').unwrap_or((line, ""));
+                if let Some(new_id) = map.items.get(id) {
+                    *line = if count.is_empty() {
+                        new_id.clone()
+                    } else {
+                        format!("{} {}", new_id, count)
+                    };
+                }
+            }
+        }
+
+        for item in &mut self.items {
+            remap(&map.items, &mut item.id);
+            remap_option(&map.dialogues, &mut item.dialogue_id);
+        }
+
+        let scripts_affected = !(map.items.is_empty()
+            && map.rooms.is_empty()
+            && map.dialogues.is_empty()
+            && map.endings.is_empty());
+
+        for dialogue in &mut self.dialogues {
+            remap(&map.dialogues, &mut dialogue.id);
+            if scripts_affected {
+                remap_script(&mut dialogue.contents, map, &ending_ids);
+            }
+        }
+
+        for ending in &mut self.endings {
+            remap(&map.endings, &mut ending.id);
+            if scripts_affected {
+                remap_script(&mut ending.dialogue, map, &ending_ids);
+            }
+        }
+    }
+
+    /// assigns dense base-36 IDs to each kind of entity in their current order
+    /// and rewrites all references. the avatar (`A`) and background tile (`0`) keep their IDs.
+    /// returns the ID changes.
+    pub fn renumber_ids(&mut self) -> IdMap {
+        let map = IdMap {
+            palettes: dense_ids(&self.palette_ids(), &[]),
+            rooms: dense_ids(&self.room_ids(), &[]),
+            tiles: dense_ids(&self.tile_ids(), &["0"]),
+            sprites: dense_ids(&self.sprite_ids(), &["A"]),
+            items: dense_ids(&self.item_ids(), &[]),
+            dialogues: dense_ids(&self.dialogue_ids(), &[]),
+            endings: dense_ids(&self.ending_ids(), &[]),
+        };
+        self.remap_ids(&map);
+        map
+    }
+
+    /// merges tiles that are pixel-identical and have the same wall and colour settings.
+    /// the first of the identical tiles is kept and room references are rewritten.
+    /// returns a map of removed tile IDs to the IDs of the tiles that replaced them.
+    pub fn dedupe_tiles(&mut self) -> BTreeMap<String, String> {
+        // tiles listed as walls in different rooms behave differently
+        let legacy_walls = |id: &str| -> Vec<&str> {
+            self.rooms
+                .iter()
+                .filter(|room| room.walls.iter().flatten().any(|wall| wall == id))
+                .map(|room| room.id.as_str())
+                .collect()
+        };
+
+        let mut replaced = BTreeMap::new();
+        for (i, tile) in self.tiles.iter().enumerate() {
+            let original = self.tiles[..i].iter().find(|other| {
+                *other == tile
+                    && !replaced.contains_key(&other.id)
+                    && legacy_walls(&other.id) == legacy_walls(&tile.id)
+            });
+            if let Some(original) = original {
+                replaced.insert(tile.id.clone(), original.id.clone());
+            }
+        }
+
+        self.tiles.retain(|tile| !replaced.contains_key(&tile.id));
+        self.remap_ids(&IdMap {
+            tiles: replaced.clone(),
+            ..IdMap::default()
+        });
+        replaced
+    }
+
+    /// merges items that are pixel-identical and have the same name, colour and dialogue.
+    /// the name must match as scripts can refer to items by name.
+    /// the first of the identical items is kept, and room, inventory and script references are rewritten.
+    /// returns a map of removed item IDs to the IDs of the items that replaced them.
+    pub fn dedupe_items(&mut self) -> BTreeMap<String, String> {
+        let mut replaced = BTreeMap::new();
+        for (i, item) in self.items.iter().enumerate() {
+            let original = self.items[..i].iter().find(|other| {
+                other.animation_frames == item.animation_frames
+                    && other.name == item.name
+                    && other.colour_id == item.colour_id
+                    && other.dialogue_id == item.dialogue_id
+                    && !replaced.contains_key(&other.id)
+            });
+            if let Some(original) = original {
+                replaced.insert(item.id.clone(), original.id.clone());
+            }
+        }
+
+        self.items.retain(|item| !replaced.contains_key(&item.id));
+        self.remap_ids(&IdMap {
+            items: replaced.clone(),
+            ..IdMap::default()
+        });
+        replaced
+    }
+
+    /// removes sprites that duplicate an earlier one: pixel-identical, with the same
+    /// name, colour, dialogue, inventory and position. as every sprite is drawn where
+    /// it is placed, sprites in different places are never merged. the avatar is kept.
+    /// nothing refers to sprites by ID, so there are no references to rewrite.
+    /// returns a map of removed sprite IDs to the IDs of the sprites that replaced them.
+    pub fn dedupe_sprites(&mut self) -> BTreeMap<String, String> {
+        let mut replaced = BTreeMap::new();
+        for (i, sprite) in self.sprites.iter().enumerate() {
+            if sprite.id == "A" {
+                continue;
+            }
+            let original = self.sprites[..i].iter().find(|other| {
+                other.id != "A"
+                    && other.animation_frames == sprite.animation_frames
+                    && other.name == sprite.name
+                    && other.colour_id == sprite.colour_id
+                    && self.sprite_dialogue_id(other) == self.sprite_dialogue_id(sprite)
+                    && other.inventory() == sprite.inventory()
+                    && other.room_id == sprite.room_id
+                    && other.position == sprite.position
+                    && !replaced.contains_key(&other.id)
+            });
+            if let Some(original) = original {
+                replaced.insert(sprite.id.clone(), original.id.clone());
+            }
+        }
+
+        self.sprites
+            .retain(|sprite| !replaced.contains_key(&sprite.id));
+        replaced
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn remap_ids_swaps() {
+        let mut game = mock::game_default();
+        game.rooms.push(mock::room());
+        let mut map = IdMap::default();
+        map.rooms.insert("0".to_string(), "a".to_string());
+        map.rooms.insert("a".to_string(), "0".to_string());
+        game.remap_ids(&map);
+
+        assert_eq!(game.room_ids(), vec!["a".to_string(), "0".to_string()]);
+        assert_eq!(game.sprites[0].room_id, Some("a".to_string()));
+    }
+
+    #[test]
+    fn remap_ids_inventory() {
+        let mut game = mock::game_default();
+        game.sprites[0].items = vec!["0 2".to_string()];
+        let mut map = IdMap::default();
+        map.items.insert("0".to_string(), "3".to_string());
+        map.dialogues.insert("1".to_string(), "5".to_string());
+        game.remap_ids(&map);
+
+        assert_eq!(game.sprites[0].items, vec!["3 2".to_string()]);
+        assert_eq!(game.items[0].id, "3");
+        assert_eq!(game.items[0].dialogue_id, Some("5".to_string()));
+        assert_eq!(game.dialogues[1].id, "5");
+    }
+
+    #[test]
+    fn remap_ids_scripts() {
+        let mut game = mock::game_default();
+        game.rooms.push(mock::room());
+        game.dialogues[0].contents = "you have {item \"0\"} keys{exit \"0\" 3 4}".to_string();
+        game.dialogues[1].contents =
+            "{\n  - {item 0} > 1 ?\n    {end \"1\"}\n  - else ?\n    one\n}".to_string();
+        game.dialogues[2].contents = "{item \"0\"".to_string();
+        let mut map = IdMap::default();
+        map.items.insert("0".to_string(), "3".to_string());
+        map.rooms.insert("0".to_string(), "a".to_string());
+        map.dialogues.insert("1".to_string(), "b".to_string());
+        game.remap_ids(&map);
+
+        assert_eq!(
+            game.dialogues[0].contents,
+            "you have {item \"3\"} keys{exit \"a\" 3 4}"
+        );
+        assert_eq!(
+            game.dialogues[1].contents,
+            "{\n  - {item 3} > 1 ?\n    {end \"b\"}\n  - else ?\n    one\n}"
+        );
+        // syntax errors are left alone
+        assert_eq!(game.dialogues[2].contents, "{item \"0\"");
+    }
+
+    #[test]
+    fn remap_ids_implicit_dialogue() {
+        let mut game = mock::game_default();
+        game.sprites[1].dialogue_id = None;
+        game.dialogues[0].id = "a".to_string();
+        let mut map = IdMap::default();
+        map.sprites.insert("a".to_string(), "b".to_string());
+        game.remap_ids(&map);
+
+        assert_eq!(game.sprites[1].id, "b");
+        assert_eq!(game.sprites[1].dialogue_id, Some("a".to_string()));
+    }
+
+    #[test]
+    fn test_dense_ids() {
+        let ids = ["0", "3", "1f", "2a"].map(|id| id.to_string());
+        let map = dense_ids(&ids, &[]);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["3"], "1");
+        assert_eq!(map["1f"], "2");
+        assert_eq!(map["2a"], "3");
+
+        let ids = ["A", "5", "0", "5"].map(|id| id.to_string());
+        let map = dense_ids(&ids, &["0", "A"]);
+        assert_eq!(map.len(), 1);
+        assert_eq!(map["5"], "1");
+    }
+
+    #[test]
+    fn renumber_ids() {
+        let mut game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        let unused = game.unused_assets();
+        game.renumber_ids();
+
+        let expected: Vec<String> = (1..=game.tiles.len() as u32).map(to_base36).collect();
+        assert_eq!(game.tile_ids(), expected);
+        assert_eq!(game.sprite_ids()[0], "A");
+        assert_eq!(game.dialogue_ids()[0], "0");
+        assert_eq!(
+            game.get_sprite("0").unwrap().dialogue_id,
+            Some("7".to_string())
+        );
+        // references are kept intact
+        let unused_after = game.unused_assets();
+        assert_eq!(unused_after.tiles.len(), unused.tiles.len());
+        assert_eq!(unused_after.dialogues.len(), unused.dialogues.len());
+        for room in &game.rooms {
+            for id in &room.tiles {
+                assert!(id == "0" || game.get_tile(id).is_some());
+            }
+        }
+
+        // renumbering is stable
+        assert!(game.renumber_ids().is_empty());
+    }
+
+    #[test]
+    fn dedupe_tiles() {
+        let mut game = mock::game_default();
+        let mut duplicate = mock::tile_default();
+        duplicate.id = "b".to_string();
+        duplicate.name = Some("another block".to_string());
+        let mut wall = mock::tile_default();
+        wall.id = "c".to_string();
+        wall.wall = Some(true);
+        game.tiles.push(duplicate);
+        game.tiles.push(wall);
+        game.rooms[0].tiles[0] = "b".to_string();
+        game.rooms[0].tiles[1] = "c".to_string();
+
+        let mut expected = BTreeMap::new();
+        expected.insert("b".to_string(), "a".to_string());
+        assert_eq!(game.dedupe_tiles(), expected);
+        assert_eq!(game.tile_ids(), vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(game.rooms[0].tiles[0], "a");
+        assert_eq!(game.rooms[0].tiles[1], "c");
+    }
+
+    #[test]
+    fn dedupe_items() {
+        let mut game = mock::game_default();
+        let mut duplicate = mock::item::key();
+        duplicate.id = "2".to_string();
+        game.items.push(duplicate);
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 3, y: 3 },
+            id: "2".to_string(),
+        });
+        game.dialogues[0].contents = "{item \"2\"} keys".to_string();
+
+        let mapping = game.dedupe_items();
+        assert_eq!(mapping.get("2"), Some(&"1".to_string()));
+        assert_eq!(game.item_ids(), vec!["0".to_string(), "1".to_string()]);
+        let placed: Vec<&str> = game.rooms[0].items.iter().map(|i| i.id.as_str()).collect();
+        assert_eq!(placed, vec!["1"]);
+        assert_eq!(game.dialogues[0].contents, "{item \"1\"} keys");
+
+        // scripts can refer to items by name, so differently named items are kept
+        let mut renamed = mock::item::key();
+        renamed.id = "3".to_string();
+        renamed.name = Some("spare key".to_string());
+        game.items.push(renamed);
+        assert!(game.dedupe_items().is_empty());
+        assert_eq!(game.item_ids().len(), 3);
+    }
+
+    #[test]
+    fn dedupe_sprites() {
+        let mut game = mock::game_default();
+        let mut duplicate = game.sprites[1].clone();
+        duplicate.id = "b".to_string();
+        let mut elsewhere = game.sprites[1].clone();
+        elsewhere.id = "c".to_string();
+        elsewhere.position = Some(Position { x: 1, y: 1 });
+        let mut avatar = game.sprites[0].clone();
+        avatar.id = "d".to_string();
+        game.sprites.push(duplicate);
+        game.sprites.push(elsewhere);
+        game.sprites.push(avatar);
+
+        let mapping = game.dedupe_sprites();
+        let mut expected = BTreeMap::new();
+        expected.insert("b".to_string(), game.sprites[1].id.clone());
+        assert_eq!(mapping, expected);
+        assert_eq!(
+            game.sprite_ids(),
+            vec![
+                "A".to_string(),
+                game.sprites[1].id.clone(),
+                "c".to_string(),
+                "d".to_string()
+            ]
+        );
+    }
+}
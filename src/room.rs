@@ -18,6 +18,15 @@ pub struct Room {
 }
 
 impl Room {
+    /// ID of the tile at the position, `0` being the empty background
+    pub fn tile_at(&self, position: Position) -> Option<&str> {
+        if !position.in_bounds() {
+            return None;
+        }
+        let index = usize::from(position.y) * 16 + usize::from(position.x);
+        self.tiles.get(index).map(|id| id.as_str())
+    }
+
     fn name_line(&self) -> String {
         optional_data_line("NAME", self.name.as_ref())
     }
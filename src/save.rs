@@ -0,0 +1,388 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::{string::String, vec::Vec};
+
+const MAGIC: &[u8; 4] = b"BSAV";
+const FORMAT_VERSION: u8 = 1;
+
+// variable value types
+const NUMBER: u8 = 0;
+const BOOLEAN: u8 = 1;
+const STRING: u8 = 2;
+
+/// everything needed to resume a `GameState`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SaveState {
+    /// `Game::content_hash()` of the game the state belongs to
+    pub game_hash: u64,
+    pub room_id: String,
+    pub position: Position,
+    pub inventory: BTreeMap<String, u32>,
+    pub variables: BTreeMap<String, VariableValue>,
+    /// items that haven't been picked up yet, by room ID
+    pub room_items: BTreeMap<String, Vec<Instance>>,
+    /// sequence, cycle and shuffle counters by dialogue ID
+    pub sequences: BTreeMap<String, Vec<u32>>,
+    pub ending_id: Option<String>,
+}
+
+impl Game {
+    /// a 64-bit FNV-1a hash of the serialised game
+    pub fn content_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf29ce484222325;
+        for byte in self.to_string().bytes() {
+            hash ^= u64::from(byte);
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+        hash
+    }
+}
+
+impl GameState<'_> {
+    pub fn save(&self) -> SaveState {
+        SaveState {
+            game_hash: self.game.content_hash(),
+            room_id: self.room_id.clone(),
+            position: self.position,
+            inventory: self.inventory.clone(),
+            variables: self.variables.clone(),
+            room_items: self.room_items.clone(),
+            sequences: self.sequences.clone(),
+            ending_id: self.ending_id.clone(),
+        }
+    }
+}
+
+impl<'a> GameState<'a> {
+    /// resumes a saved state. fails if the state belongs to a different game.
+    pub fn restore(game: &'a Game, save: &SaveState) -> Result<Self, crate::Error> {
+        if save.game_hash != game.content_hash() || !save.position.in_bounds() {
+            return Err(crate::Error::Save);
+        }
+        if game.get_room(&save.room_id).is_none() {
+            return Err(crate::Error::Game {
+                missing: NotFound::Room,
+            });
+        }
+        Ok(Self {
+            game,
+            room_id: save.room_id.clone(),
+            position: save.position,
+            inventory: save.inventory.clone(),
+            room_items: save.room_items.clone(),
+            variables: save.variables.clone(),
+            sequences: save.sequences.clone(),
+            ending_id: save.ending_id.clone(),
+        })
+    }
+
+    pub fn load(game: &'a Game, bytes: &[u8]) -> Result<Self, crate::Error> {
+        Self::restore(game, &SaveState::decode(bytes)?)
+    }
+}
+
+fn write_number(bytes: &mut Vec<u8>, mut number: u64) {
+    loop {
+        let byte = (number & 0x7f) as u8;
+        number >>= 7;
+        if number == 0 {
+            bytes.push(byte);
+            return;
+        }
+        bytes.push(byte | 0x80);
+    }
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    write_number(bytes, string.len() as u64);
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+fn write_position(bytes: &mut Vec<u8>, position: Position) {
+    bytes.push(position.x);
+    bytes.push(position.y);
+}
+
+/// reads the binary format, failing on any malformed or truncated data
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl Reader<'_> {
+    fn byte(&mut self) -> Result<u8, crate::Error> {
+        let (first, rest) = self.bytes.split_first().ok_or(crate::Error::Save)?;
+        self.bytes = rest;
+        Ok(*first)
+    }
+
+    fn number(&mut self) -> Result<u64, crate::Error> {
+        let mut number: u64 = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            number |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(number);
+            }
+        }
+        Err(crate::Error::Save)
+    }
+
+    fn u32(&mut self) -> Result<u32, crate::Error> {
+        u32::try_from(self.number()?).map_err(|_| crate::Error::Save)
+    }
+
+    /// a count of entries, which can't be more than the remaining bytes
+    fn len(&mut self) -> Result<usize, crate::Error> {
+        let len = usize::try_from(self.number()?).map_err(|_| crate::Error::Save)?;
+        if len > self.bytes.len() {
+            return Err(crate::Error::Save);
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, crate::Error> {
+        let len = self.len()?;
+        let (string, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        core::str::from_utf8(string)
+            .map(|s| s.to_owned())
+            .map_err(|_| crate::Error::Save)
+    }
+
+    fn position(&mut self) -> Result<Position, crate::Error> {
+        Ok(Position {
+            x: self.byte()?,
+            y: self.byte()?,
+        })
+    }
+}
+
+impl SaveState {
+    /// a compact binary encoding: a magic number and format version,
+    /// then the fields with variable-length numbers and length-prefixed strings
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(&MAGIC[..]);
+        bytes.push(FORMAT_VERSION);
+        bytes.extend_from_slice(&self.game_hash.to_le_bytes());
+
+        write_string(&mut bytes, &self.room_id);
+        write_position(&mut bytes, self.position);
+        match &self.ending_id {
+            Some(id) => {
+                bytes.push(1);
+                write_string(&mut bytes, id);
+            }
+            None => bytes.push(0),
+        }
+
+        write_number(&mut bytes, self.inventory.len() as u64);
+        for (id, count) in &self.inventory {
+            write_string(&mut bytes, id);
+            write_number(&mut bytes, u64::from(*count));
+        }
+
+        write_number(&mut bytes, self.variables.len() as u64);
+        for (id, value) in &self.variables {
+            write_string(&mut bytes, id);
+            bytes.push(match value {
+                VariableValue::Number { .. } => NUMBER,
+                VariableValue::Boolean(_) => BOOLEAN,
+                VariableValue::String(_) => STRING,
+            });
+            write_string(&mut bytes, &value.to_string());
+        }
+
+        write_number(&mut bytes, self.room_items.len() as u64);
+        for (room_id, items) in &self.room_items {
+            write_string(&mut bytes, room_id);
+            write_number(&mut bytes, items.len() as u64);
+            for item in items {
+                write_string(&mut bytes, &item.id);
+                write_position(&mut bytes, item.position);
+            }
+        }
+
+        write_number(&mut bytes, self.sequences.len() as u64);
+        for (dialogue_id, counters) in &self.sequences {
+            write_string(&mut bytes, dialogue_id);
+            write_number(&mut bytes, counters.len() as u64);
+            for counter in counters {
+                write_number(&mut bytes, u64::from(*counter));
+            }
+        }
+
+        bytes
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Self, crate::Error> {
+        let rest = bytes.strip_prefix(&MAGIC[..]).ok_or(crate::Error::Save)?;
+        let mut reader = Reader { bytes: rest };
+        if reader.byte()? != FORMAT_VERSION || reader.bytes.len() < 8 {
+            return Err(crate::Error::Save);
+        }
+        let (hash, rest) = reader.bytes.split_at(8);
+        reader.bytes = rest;
+        let game_hash = u64::from_le_bytes(hash.try_into().map_err(|_| crate::Error::Save)?);
+
+        let room_id = reader.string()?;
+        let position = reader.position()?;
+        let ending_id = match reader.byte()? {
+            0 => None,
+            1 => Some(reader.string()?),
+            _ => return Err(crate::Error::Save),
+        };
+
+        let mut inventory = BTreeMap::new();
+        for _ in 0..reader.len()? {
+            inventory.insert(reader.string()?, reader.u32()?);
+        }
+
+        let mut variables = BTreeMap::new();
+        for _ in 0..reader.len()? {
+            let id = reader.string()?;
+            let tag = reader.byte()?;
+            let text = reader.string()?;
+            let value = match tag {
+                NUMBER => VariableValue::Number {
+                    value: text.parse().map_err(|_| crate::Error::Save)?,
+                    text,
+                },
+                BOOLEAN => match text.as_str() {
+                    "true" => VariableValue::Boolean(true),
+                    "false" => VariableValue::Boolean(false),
+                    _ => return Err(crate::Error::Save),
+                },
+                STRING => VariableValue::String(text),
+                _ => return Err(crate::Error::Save),
+            };
+            variables.insert(id, value);
+        }
+
+        let mut room_items = BTreeMap::new();
+        for _ in 0..reader.len()? {
+            let room_id = reader.string()?;
+            let mut items = Vec::new();
+            for _ in 0..reader.len()? {
+                let id = reader.string()?;
+                let position = reader.position()?;
+                items.push(Instance { position, id });
+            }
+            room_items.insert(room_id, items);
+        }
+
+        let mut sequences = BTreeMap::new();
+        for _ in 0..reader.len()? {
+            let dialogue_id = reader.string()?;
+            let mut counters = Vec::new();
+            for _ in 0..reader.len()? {
+                counters.push(reader.u32()?);
+            }
+            sequences.insert(dialogue_id, counters);
+        }
+
+        if !reader.bytes.is_empty() {
+            return Err(crate::Error::Save);
+        }
+
+        Ok(SaveState {
+            game_hash,
+            room_id,
+            position,
+            inventory,
+            variables,
+            room_items,
+            sequences,
+            ending_id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 5, y: 4 },
+            id: "0".to_string(),
+        });
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 9, y: 9 },
+            id: "1".to_string(),
+        });
+        game
+    }
+
+    #[test]
+    fn save_and_load() {
+        let game = game();
+        let mut state = game.play().unwrap();
+        state.step(Direction::Right);
+        state.set_variable("a", VariableValue::from(43.0));
+        state.set_sequence_counter("0", 1, 2);
+
+        let save = state.save();
+        assert_eq!(save.variables.get("a").unwrap().as_number(), Some(43.0));
+        assert_eq!(save.room_items.get("0").unwrap().len(), 1);
+
+        let bytes = save.encode();
+        assert_eq!(SaveState::decode(&bytes), Ok(save));
+        let loaded = GameState::load(&game, &bytes).unwrap();
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.item_count("0"), 1);
+        assert_eq!(loaded.sequence_counter("0", 1), 2);
+        assert_eq!(loaded.sequence_counter("0", 0), 0);
+    }
+
+    #[test]
+    fn variable_types() {
+        let game = game();
+        let mut state = game.play().unwrap();
+        state.set_variable("a", VariableValue::String("42".to_string()));
+        state.set_variable("b", VariableValue::String("true".to_string()));
+        state.set_variable("c", VariableValue::Boolean(false));
+
+        let save = state.save();
+        let decoded = SaveState::decode(&save.encode()).unwrap();
+        assert_eq!(decoded, save);
+        assert_eq!(
+            decoded.variables.get("a"),
+            Some(&VariableValue::String("42".to_string()))
+        );
+        assert_eq!(
+            decoded.variables.get("b"),
+            Some(&VariableValue::String("true".to_string()))
+        );
+    }
+
+    #[test]
+    fn variables_are_seeded() {
+        let game = game();
+        let save = game.play().unwrap().save();
+        assert_eq!(save.variables.get("a").unwrap().to_string(), "42");
+    }
+
+    #[test]
+    fn different_game() {
+        let game = game();
+        let bytes = game.play().unwrap().save().encode();
+        let mut other = game.clone();
+        other.name = "another game".to_string();
+        assert_eq!(GameState::load(&other, &bytes), Err(crate::Error::Save));
+    }
+
+    #[test]
+    fn malformed() {
+        let bytes = game().play().unwrap().save().encode();
+        for len in 0..bytes.len() {
+            assert_eq!(SaveState::decode(&bytes[..len]), Err(crate::Error::Save));
+        }
+        let mut bytes = bytes;
+        bytes.push(0);
+        assert_eq!(SaveState::decode(&bytes), Err(crate::Error::Save));
+    }
+}
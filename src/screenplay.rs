@@ -0,0 +1,297 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec::Vec};
+
+/// text effects, which don't change what is said
+const EFFECTS: [&str; 7] = ["wvy", "shk", "rbw", "clr", "clr1", "clr2", "clr3"];
+
+fn is_effect(name: &str) -> bool {
+    EFFECTS.contains(&name.trim_start_matches('/'))
+}
+
+/// appends text to the last line, starting new lines at line breaks
+fn push_text(lines: &mut Vec<String>, text: &str) {
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 || lines.is_empty() {
+            lines.push(String::new());
+        }
+        let last = lines.last_mut().unwrap();
+        // indentation in the source isn't part of the text
+        last.push_str(if last.is_empty() {
+            line.trim_start()
+        } else {
+            line
+        });
+    }
+}
+
+/// markdown lines for a script, with sequences and conditionals as lists
+fn script_lines(nodes: &[Node]) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for node in nodes {
+        match &node.kind {
+            NodeKind::Text(text) => push_text(&mut lines, text),
+            NodeKind::Function { name, .. } if is_effect(name) => {}
+            NodeKind::Function { name, .. } if name == "br" => lines.push(String::new()),
+            NodeKind::Function { name, .. } if name == "pg" => {
+                lines.push(String::new());
+                lines.push(String::new());
+            }
+            NodeKind::Function { .. } | NodeKind::Assignment { .. } | NodeKind::Expression(_) => {
+                push_text(&mut lines, &format!("`{}`", node));
+            }
+            NodeKind::Sequence { kind, options, .. } => {
+                lines.push(String::new());
+                lines.push(format!("- *{}*", kind.as_str()));
+                for option in options {
+                    push_branch(&mut lines, &option.body);
+                }
+                lines.push(String::new());
+            }
+            NodeKind::Conditional { branches, .. } => {
+                lines.push(String::new());
+                for (i, branch) in branches.iter().enumerate() {
+                    let label = match (&branch.condition, i) {
+                        (Some(condition), 0) => format!("*if* `{}`", condition),
+                        (Some(condition), _) => format!("*else if* `{}`", condition),
+                        (None, _) => "*else*".to_owned(),
+                    };
+                    lines.push(format!("- {}", label));
+                    push_branch(&mut lines, &branch.body);
+                }
+                lines.push(String::new());
+            }
+        }
+    }
+    tidy(lines)
+}
+
+/// a list item for a sequence option or a conditional branch
+fn push_branch(lines: &mut Vec<String>, body: &[Node]) {
+    let body = script_lines(body);
+    let Some(first) = body.first() else {
+        lines.push("  - *(nothing)*".to_owned());
+        return;
+    };
+    let rest = if first.starts_with('-') {
+        // a nested list can't share a line with its parent item
+        lines.push("  -".to_owned());
+        &body[..]
+    } else {
+        lines.push(format!("  - {}", first));
+        &body[1..]
+    };
+    for line in rest {
+        if line.is_empty() {
+            lines.push(String::new());
+        } else {
+            lines.push(format!("    {}", line));
+        }
+    }
+}
+
+/// trims line ends and drops leading, trailing and repeated blank lines
+fn tidy(lines: Vec<String>) -> Vec<String> {
+    let mut tidied: Vec<String> = Vec::new();
+    for line in lines {
+        let line = line.trim_end();
+        if line.is_empty() && tidied.last().is_none_or(|l| l.is_empty()) {
+            continue;
+        }
+        tidied.push(line.to_owned());
+    }
+    while tidied.last().is_some_and(|l| l.is_empty()) {
+        tidied.pop();
+    }
+    tidied
+}
+
+/// the dialogue as markdown. scripts that can't be parsed are shown as they are.
+fn dialogue_markdown(contents: &str) -> String {
+    let lines = match Script::parse(contents) {
+        Ok(script) => script_lines(&script.nodes),
+        Err(_) => contents.lines().map(|l| l.trim().to_owned()).collect(),
+    };
+    lines.join("\n")
+}
+
+fn name_or(name: &Option<String>, kind: &str, id: &str) -> String {
+    match name {
+        Some(name) => name.clone(),
+        None => format!("{} {}", kind, id),
+    }
+}
+
+impl Game {
+    fn dialogue_text(&self, id: &str) -> Option<&str> {
+        self.dialogues
+            .iter()
+            .find(|d| d.id == id)
+            .map(|d| d.contents.as_str())
+    }
+
+    /// the game's dialogue as a markdown document for proofreading.
+    ///
+    /// dialogue is grouped by room, under the name of the sprite or item that
+    /// says it, followed by exit dialogue and endings. text effects are left out,
+    /// sequences and conditionals are shown as lists and other code as `code`.
+    /// dialogue that isn't placed in any room comes last.
+    pub fn to_markdown(&self) -> String {
+        let mut sections: Vec<String> = Vec::new();
+        let mut shown: Vec<&str> = Vec::new();
+        let mut section = |heading: String, contents: &str| {
+            sections.push(format!("{}\n\n{}", heading, dialogue_markdown(contents)));
+        };
+
+        for room in &self.rooms {
+            section(format!("## {}", name_or(&room.name, "room", &room.id)), "");
+            let sprites = self
+                .sprites
+                .iter()
+                .filter(|s| s.room_id.as_ref() == Some(&room.id));
+            for sprite in sprites {
+                if let Some(id) = self.sprite_dialogue_id(sprite)
+                    && let Some(text) = self.dialogue_text(id)
+                {
+                    section(
+                        format!("### {}", name_or(&sprite.name, "sprite", &sprite.id)),
+                        text,
+                    );
+                    shown.push(id);
+                }
+            }
+
+            let mut items: Vec<&str> = Vec::new();
+            for instance in &room.items {
+                if !items.contains(&instance.id.as_str()) {
+                    items.push(&instance.id);
+                }
+            }
+            for item in items.iter().filter_map(|id| self.get_item(id)) {
+                if let Some(id) = &item.dialogue_id
+                    && let Some(text) = self.dialogue_text(id)
+                {
+                    section(
+                        format!("### {}", name_or(&item.name, "item", &item.id)),
+                        text,
+                    );
+                    shown.push(id);
+                }
+            }
+
+            for exit in &room.exits {
+                if let Some(id) = &exit.dialogue_id
+                    && let Some(text) = self.dialogue_text(id)
+                {
+                    let destination = self
+                        .get_room(&exit.exit.room_id)
+                        .map(|r| name_or(&r.name, "room", &r.id))
+                        .unwrap_or_else(|| format!("room {}", exit.exit.room_id));
+                    section(format!("### exit to {}", destination), text);
+                    shown.push(id);
+                }
+            }
+
+            for instance in &room.endings {
+                let text = match self.endings.iter().find(|e| e.id == instance.id) {
+                    Some(ending) => Some(ending.dialogue.as_str()),
+                    // since Bitsy 8, endings are dialogues
+                    None => self.dialogue_text(&instance.id),
+                };
+                if let Some(text) = text {
+                    section(format!("### ending {}", instance.id), text);
+                    shown.push(&instance.id);
+                }
+            }
+        }
+
+        let unplaced_dialogues = self
+            .dialogues
+            .iter()
+            .filter(|d| !shown.contains(&d.id.as_str()))
+            .map(|d| (&d.id, &d.contents));
+        let unplaced_endings = self
+            .endings
+            .iter()
+            .filter(|e| !shown.contains(&e.id.as_str()))
+            .map(|e| (&e.id, &e.dialogue));
+        let mut unplaced = unplaced_dialogues.chain(unplaced_endings).peekable();
+        if unplaced.peek().is_some() {
+            section("## elsewhere".to_owned(), "");
+            for (id, text) in unplaced {
+                section(format!("### dialogue {}", id), text);
+            }
+        }
+
+        let mut markdown = format!("# {}\n", self.name);
+        for section in sections {
+            markdown.push('\n');
+            markdown.push_str(section.trim_end());
+            markdown.push('\n');
+        }
+        markdown
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::mock;
+
+    #[test]
+    fn default_game() {
+        let expected = "# Write your game's title here
+
+## example room
+
+### cat
+
+I'm a cat
+
+## elsewhere
+
+### dialogue 1
+
+hi
+
+### dialogue 2
+
+A key! What does it open?
+";
+        let mut game = mock::game_default();
+        game.dialogues[1].contents = "hi".into();
+        assert_eq!(game.to_markdown(), expected);
+    }
+
+    #[test]
+    fn structure() {
+        let mut game = mock::game_default();
+        game.dialogues[0].contents = "\"\"\"
+{wvy}hello{/wvy} {br}there
+{sequence
+  - first time{a = a + 1}
+  - {
+    - a > 1 ? again
+    - else ?
+  }
+}
+bye {say \"now\"}
+\"\"\""
+            .into();
+        let expected = "### cat
+
+hello
+there
+
+- *sequence*
+  - first time`{a = a + 1}`
+  -
+    - *if* `a > 1`
+      - again
+    - *else*
+      - *(nothing)*
+
+bye `{say \"now\"}`";
+        let markdown = game.to_markdown();
+        assert!(markdown.contains(expected), "{}", markdown);
+    }
+}
@@ -0,0 +1,1022 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
+/// byte offsets into the parsed source
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SyntaxError {
+    pub message: &'static str,
+    pub span: Span,
+}
+
+impl fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum Operator {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+}
+
+impl Operator {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operator::Equal => "==",
+            Operator::Less => "<",
+            Operator::LessOrEqual => "<=",
+            Operator::Greater => ">",
+            Operator::GreaterOrEqual => ">=",
+            Operator::Add => "+",
+            Operator::Subtract => "-",
+            Operator::Multiply => "*",
+            Operator::Divide => "/",
+        }
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Multiply | Operator::Divide => 3,
+            Operator::Add | Operator::Subtract => 2,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expression {
+    /// kept as written, e.g. `1.50`
+    Number(String),
+    Boolean(bool),
+    String(String),
+    Variable(String),
+    /// a function call used as a value, e.g. `{item "1"}`
+    Call {
+        name: String,
+        arguments: Vec<Expression>,
+    },
+    Binary {
+        operator: Operator,
+        left: Box<Expression>,
+        right: Box<Expression>,
+    },
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expression::Number(number) => write!(f, "{}", number),
+            Expression::Boolean(boolean) => write!(f, "{}", boolean),
+            Expression::String(string) => write!(f, "\"{}\"", string),
+            Expression::Variable(name) => write!(f, "{}", name),
+            Expression::Call { name, arguments } => {
+                write!(f, "{{{}", name)?;
+                for argument in arguments {
+                    write!(f, " {}", argument)?;
+                }
+                write!(f, "}}")
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => write!(f, "{} {} {}", left, operator.as_str(), right),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub enum SequenceKind {
+    /// each option once, then the last one forever
+    Sequence,
+    /// each option in turn, then from the start again
+    Cycle,
+    Shuffle,
+}
+
+impl SequenceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SequenceKind::Sequence => "sequence",
+            SequenceKind::Cycle => "cycle",
+            SequenceKind::Shuffle => "shuffle",
+        }
+    }
+
+    fn from(name: &str) -> Option<SequenceKind> {
+        match name {
+            "sequence" => Some(SequenceKind::Sequence),
+            "cycle" => Some(SequenceKind::Cycle),
+            "shuffle" => Some(SequenceKind::Shuffle),
+            _ => None,
+        }
+    }
+}
+
+/// an option of a sequence or a branch of a conditional, starting with `-`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Branch {
+    /// whitespace before the `-`, usually a line break and indentation
+    pub indent: String,
+    /// `None` for `else` and for sequence options
+    pub condition: Option<Expression>,
+    /// whitespace before each token after the `-`, or, for a sequence option,
+    /// between the `-` and the body
+    pub spacing: Vec<String>,
+    pub body: Vec<Node>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NodeKind {
+    /// text shown to the player, including line breaks
+    Text(String),
+    /// `{name arguments}`, e.g. `{wvy}`, `{/wvy}`, `{br}`, `{pg}`, `{say "hi"}`
+    Function {
+        name: String,
+        arguments: Vec<Expression>,
+    },
+    /// `{a = a + 1}`
+    Assignment { variable: String, value: Expression },
+    /// code that is only a value, e.g. `{a + 1}`
+    Expression(Expression),
+    /// `{sequence ...}`, `{cycle ...}` or `{shuffle ...}`
+    Sequence {
+        kind: SequenceKind,
+        options: Vec<Branch>,
+        /// whitespace before the closing `}`
+        closing: String,
+    },
+    /// `{ - condition ? ... - else ? ...}`
+    Conditional {
+        branches: Vec<Branch>,
+        closing: String,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub span: Span,
+    /// whitespace before each token of the code after the opening `{`.
+    /// tokens without any, e.g. ones added by editing the node, get single spaces
+    pub spacing: Vec<String>,
+}
+
+/// a parsed dialogue script.
+///
+/// printing it gives back the source as it was written. code that has been
+/// edited is written with single spaces, as Bitsy writes it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Script {
+    /// whether the script is wrapped in `"""` lines, for multi-line dialogue
+    pub quoted: bool,
+    pub nodes: Vec<Node>,
+}
+
+impl Script {
+    pub fn parse(source: &str) -> Result<Script, SyntaxError> {
+        let (quoted, start, end) = match source
+            .strip_prefix("\"\"\"\n")
+            .and_then(|s| s.strip_suffix("\n\"\"\""))
+        {
+            Some(body) => (true, 4, 4 + body.len()),
+            // an empty quoted script is just two lines of quotes
+            None if source == "\"\"\"\n\"\"\"" => (true, 4, 4),
+            None => (false, 0, source.len()),
+        };
+        let mut parser = Parser {
+            source: &source[..end],
+            position: start,
+        };
+        let nodes = parser.nodes(false)?;
+        Ok(Script { quoted, nodes })
+    }
+
+    /// calls the function for every node, including those nested in branches
+    pub fn visit<'a>(&'a self, f: &mut impl FnMut(&'a Node)) {
+        visit(&self.nodes, f);
+    }
+}
+
+fn visit<'a>(nodes: &'a [Node], f: &mut impl FnMut(&'a Node)) {
+    for node in nodes {
+        f(node);
+        match &node.kind {
+            NodeKind::Sequence {
+                options: branches, ..
+            }
+            | NodeKind::Conditional { branches, .. } => {
+                for branch in branches {
+                    visit(&branch.body, f);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.quoted {
+            writeln!(f, "\"\"\"")?;
+        }
+        for node in &self.nodes {
+            write!(f, "{}", node)?;
+        }
+        if self.quoted {
+            write!(f, "\n\"\"\"")?;
+        }
+        Ok(())
+    }
+}
+
+fn write_body(f: &mut fmt::Formatter<'_>, nodes: &[Node]) -> fmt::Result {
+    nodes.iter().try_for_each(|node| write!(f, "{}", node))
+}
+
+/// writes code token by token, each with the whitespace that was before it in the source.
+/// the last token, e.g. the closing `}`, always gets the last whitespace,
+/// so that code stays the same at its edges when tokens are added or removed.
+struct Tokens<'a, 'b> {
+    f: &'a mut fmt::Formatter<'b>,
+    spacing: &'a [String],
+    index: usize,
+    /// whitespace for the next token if the source had none recorded
+    space: &'static str,
+}
+
+impl<'a, 'b> Tokens<'a, 'b> {
+    fn new(f: &'a mut fmt::Formatter<'b>, spacing: &'a [String], space: &'static str) -> Self {
+        Tokens {
+            f,
+            spacing,
+            index: 0,
+            space,
+        }
+    }
+
+    fn token(&mut self, token: &str) -> fmt::Result {
+        let space = match self.spacing.split_last() {
+            Some((_, middle)) => middle.get(self.index),
+            None => None,
+        };
+        let space = space.map_or(self.space, String::as_str);
+        self.index += 1;
+        self.space = " ";
+        write!(self.f, "{}{}", space, token)
+    }
+
+    fn last_token(&mut self, token: &str) -> fmt::Result {
+        let space = self.spacing.last().map_or(self.space, String::as_str);
+        write!(self.f, "{}{}", space, token)
+    }
+
+    fn close(&mut self) -> fmt::Result {
+        self.space = "";
+        self.last_token("}")
+    }
+
+    fn name(&mut self, name: &str) -> fmt::Result {
+        // closing effect tags like `{/wvy}` are a `/` and a name
+        match name.strip_prefix('/') {
+            Some(name) => {
+                self.token("/")?;
+                self.space = "";
+                self.token(name)
+            }
+            None => self.token(name),
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) -> fmt::Result {
+        match expression {
+            Expression::Number(number) => match number.strip_prefix('-') {
+                Some(number) => {
+                    self.token("-")?;
+                    self.space = "";
+                    self.token(number)
+                }
+                None => self.token(number),
+            },
+            Expression::Boolean(boolean) => self.token(if *boolean { "true" } else { "false" }),
+            Expression::String(string) => self.token(&format!("\"{}\"", string)),
+            Expression::Variable(name) => self.token(name),
+            Expression::Call { name, arguments } => {
+                self.token("{")?;
+                self.space = "";
+                self.name(name)?;
+                for argument in arguments {
+                    self.expression(argument)?;
+                }
+                self.space = "";
+                self.token("}")
+            }
+            Expression::Binary {
+                operator,
+                left,
+                right,
+            } => {
+                self.expression(left)?;
+                self.token(operator.as_str())?;
+                self.expression(right)
+            }
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let NodeKind::Text(text) = &self.kind {
+            return write!(f, "{}", text);
+        }
+        write!(f, "{{")?;
+        let mut tokens = Tokens::new(f, &self.spacing, "");
+        match &self.kind {
+            NodeKind::Text(_) => Ok(()),
+            NodeKind::Function { name, arguments } => {
+                tokens.name(name)?;
+                for argument in arguments {
+                    tokens.expression(argument)?;
+                }
+                tokens.close()
+            }
+            NodeKind::Assignment { variable, value } => {
+                tokens.token(variable)?;
+                tokens.token("=")?;
+                tokens.expression(value)?;
+                tokens.close()
+            }
+            NodeKind::Expression(expression) => {
+                tokens.expression(expression)?;
+                tokens.close()
+            }
+            NodeKind::Sequence {
+                kind,
+                options,
+                closing,
+            } => {
+                tokens.last_token(kind.as_str())?;
+                for option in options {
+                    let space = option.spacing.first().map_or(" ", String::as_str);
+                    write!(f, "{}-{}", option.indent, space)?;
+                    write_body(f, &option.body)?;
+                }
+                write!(f, "{}}}", closing)
+            }
+            NodeKind::Conditional { branches, closing } => {
+                for branch in branches {
+                    write!(f, "{}-", branch.indent)?;
+                    let mut tokens = Tokens::new(f, &branch.spacing, " ");
+                    match &branch.condition {
+                        Some(condition) => tokens.expression(condition)?,
+                        None => tokens.token("else")?,
+                    }
+                    tokens.last_token("?")?;
+                    write_body(f, &branch.body)?;
+                }
+                write!(f, "{}}}", closing)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Identifier(String),
+    Number(String),
+    String(String),
+    Operator(Operator),
+    Assign,
+    OpenBrace,
+    CloseBrace,
+    Question,
+    End,
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str {
+        &self.source[self.position..]
+    }
+
+    fn error<T>(&self, message: &'static str, start: usize) -> Result<T, SyntaxError> {
+        Err(SyntaxError {
+            message,
+            span: Span {
+                start,
+                end: self.position.max(start),
+            },
+        })
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.position += rest.len() - rest.trim_start().len();
+    }
+
+    /// text and code up to the end of the source, or, inside a sequence or
+    /// conditional, up to the next `-` line or the closing `}`
+    fn nodes(&mut self, in_block: bool) -> Result<Vec<Node>, SyntaxError> {
+        let mut nodes = Vec::new();
+        let mut text_start = self.position;
+        loop {
+            let rest = self.rest();
+            let Some(c) = rest.chars().next() else {
+                break;
+            };
+            if in_block && c == '}' {
+                break;
+            }
+            if in_block && c == '\n' {
+                let after = rest.trim_start();
+                if after.starts_with('-') || after.starts_with('}') {
+                    break;
+                }
+            }
+            if c == '{' {
+                push_text(&mut nodes, self.source, text_start, self.position);
+                nodes.push(self.code()?);
+                text_start = self.position;
+                continue;
+            }
+            self.position += c.len_utf8();
+        }
+        push_text(&mut nodes, self.source, text_start, self.position);
+        Ok(nodes)
+    }
+
+    /// a `{...}` block, starting at the `{`
+    fn code(&mut self) -> Result<Node, SyntaxError> {
+        let start = self.position;
+        self.position += 1;
+
+        let after_brace = self.position;
+        self.skip_whitespace();
+        // a `-` only starts a branch at the start of a line, so `{-1}` is a number
+        let new_line = self.source[after_brace..self.position].contains('\n');
+        if new_line && self.rest().starts_with('-') {
+            self.position = after_brace;
+            let (branches, closing) = self.branches(true)?;
+            return Ok(Node {
+                kind: NodeKind::Conditional { branches, closing },
+                span: Span {
+                    start,
+                    end: self.position,
+                },
+                spacing: Vec::new(),
+            });
+        }
+
+        // closing effect tags like `{/wvy}`
+        if let Some(name) = self.rest().strip_prefix('/') {
+            let len = identifier_len(name);
+            if len > 0 {
+                let name = self.rest()[..=len].to_owned();
+                self.position += len + 1;
+                return self.finish_function(start, name);
+            }
+        }
+
+        let token_start = self.position;
+        match self.token()? {
+            Token::Identifier(name) => {
+                if let Some(kind) = SequenceKind::from(&name) {
+                    let spacing = self.spacing(start + 1, self.position);
+                    let (options, closing) = self.branches(false)?;
+                    return Ok(Node {
+                        kind: NodeKind::Sequence {
+                            kind,
+                            options,
+                            closing,
+                        },
+                        span: Span {
+                            start,
+                            end: self.position,
+                        },
+                        spacing,
+                    });
+                }
+                let after_name = self.position;
+                match self.token()? {
+                    Token::Assign => {
+                        let value = self.expression()?;
+                        self.expect_close(start)?;
+                        Ok(Node {
+                            kind: NodeKind::Assignment {
+                                variable: name,
+                                value,
+                            },
+                            span: Span {
+                                start,
+                                end: self.position,
+                            },
+                            spacing: self.spacing(start + 1, self.position),
+                        })
+                    }
+                    Token::Operator(_) => {
+                        self.position = token_start;
+                        self.finish_expression(start)
+                    }
+                    _ => {
+                        self.position = after_name;
+                        self.finish_function(start, name)
+                    }
+                }
+            }
+            Token::CloseBrace => self.error("empty code block", start),
+            _ => {
+                self.position = token_start;
+                self.finish_expression(start)
+            }
+        }
+    }
+
+    fn finish_function(&mut self, start: usize, name: String) -> Result<Node, SyntaxError> {
+        let mut arguments = Vec::new();
+        loop {
+            let before = self.position;
+            match self.token()? {
+                Token::CloseBrace => break,
+                Token::End => return self.error("code block is not closed", start),
+                _ => {
+                    self.position = before;
+                    arguments.push(self.expression()?);
+                }
+            }
+        }
+        Ok(Node {
+            kind: NodeKind::Function { name, arguments },
+            span: Span {
+                start,
+                end: self.position,
+            },
+            spacing: self.spacing(start + 1, self.position),
+        })
+    }
+
+    fn finish_expression(&mut self, start: usize) -> Result<Node, SyntaxError> {
+        let expression = self.expression()?;
+        self.expect_close(start)?;
+        Ok(Node {
+            kind: NodeKind::Expression(expression),
+            span: Span {
+                start,
+                end: self.position,
+            },
+            spacing: self.spacing(start + 1, self.position),
+        })
+    }
+
+    fn expect_close(&mut self, start: usize) -> Result<(), SyntaxError> {
+        let before = self.position;
+        match self.token()? {
+            Token::CloseBrace => Ok(()),
+            Token::End => self.error("code block is not closed", start),
+            _ => self.error("expected `}`", before),
+        }
+    }
+
+    /// the `-` options of a sequence or the branches of a conditional, up to and including `}`
+    fn branches(&mut self, conditional: bool) -> Result<(Vec<Branch>, String), SyntaxError> {
+        let mut branches = Vec::new();
+        loop {
+            let indent_start = self.position;
+            self.skip_whitespace();
+            let indent = self.source[indent_start..self.position].to_owned();
+            let branch_start = self.position;
+            match self.rest().chars().next() {
+                Some('}') => {
+                    self.position += 1;
+                    if branches.is_empty() {
+                        return self.error("expected `-`", branch_start);
+                    }
+                    return Ok((branches, indent));
+                }
+                Some('-') => self.position += 1,
+                Some(_) => return self.error("expected `-`", branch_start),
+                None => return self.error("code block is not closed", branch_start),
+            }
+
+            let after_dash = self.position;
+            let condition = if conditional {
+                self.skip_whitespace();
+                let condition_start = self.position;
+                let condition = match self.rest().strip_prefix("else") {
+                    Some(rest) if rest.trim_start().starts_with('?') => {
+                        self.position += 4;
+                        None
+                    }
+                    _ => Some(self.expression()?),
+                };
+                if self.token()? != Token::Question {
+                    return self.error("expected `?` after condition", condition_start);
+                }
+                condition
+            } else {
+                let rest = self.rest();
+                self.position += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+                None
+            };
+            let spacing = if conditional {
+                self.spacing(after_dash, self.position)
+            } else {
+                vec![self.source[after_dash..self.position].to_owned()]
+            };
+
+            let body = self.nodes(true)?;
+            branches.push(Branch {
+                indent,
+                condition,
+                spacing,
+                body,
+                span: Span {
+                    start: branch_start,
+                    end: self.position,
+                },
+            });
+        }
+    }
+
+    /// the whitespace before each token between the offsets, for printing the code as written
+    fn spacing(&self, start: usize, end: usize) -> Vec<String> {
+        let mut lexer = Parser {
+            source: &self.source[..end],
+            position: start,
+        };
+        let mut spacing = Vec::new();
+        loop {
+            let before = lexer.position;
+            lexer.skip_whitespace();
+            if lexer.position >= end {
+                return spacing;
+            }
+            spacing.push(self.source[before..lexer.position].to_owned());
+            if lexer.token().is_err() {
+                return spacing;
+            }
+        }
+    }
+
+    fn expression(&mut self) -> Result<Expression, SyntaxError> {
+        self.binary(1)
+    }
+
+    /// precedence climbing over binary operators
+    fn binary(&mut self, min_precedence: u8) -> Result<Expression, SyntaxError> {
+        let mut left = self.primary()?;
+        loop {
+            let before = self.position;
+            let operator = match self.token()? {
+                Token::Operator(operator) if operator.precedence() >= min_precedence => operator,
+                _ => {
+                    self.position = before;
+                    return Ok(left);
+                }
+            };
+            let right = self.binary(operator.precedence() + 1)?;
+            left = Expression::Binary {
+                operator,
+                left: Box::new(left),
+                right: Box::new(right),
+            };
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expression, SyntaxError> {
+        let start = self.position;
+        match self.token()? {
+            Token::Number(number) => Ok(Expression::Number(number)),
+            Token::String(string) => Ok(Expression::String(string)),
+            Token::Identifier(name) if name == "true" => Ok(Expression::Boolean(true)),
+            Token::Identifier(name) if name == "false" => Ok(Expression::Boolean(false)),
+            Token::Identifier(name) => Ok(Expression::Variable(name)),
+            Token::Operator(Operator::Subtract) => match self.token()? {
+                Token::Number(number) => Ok(Expression::Number(["-", &number].concat())),
+                _ => self.error("expected a number after `-`", start),
+            },
+            Token::OpenBrace => {
+                let name = match self.token()? {
+                    Token::Identifier(name) => name,
+                    _ => return self.error("expected a function name", start),
+                };
+                let mut arguments = Vec::new();
+                loop {
+                    let before = self.position;
+                    match self.token()? {
+                        Token::CloseBrace => break,
+                        Token::End => return self.error("code block is not closed", start),
+                        _ => {
+                            self.position = before;
+                            arguments.push(self.expression()?);
+                        }
+                    }
+                }
+                Ok(Expression::Call { name, arguments })
+            }
+            Token::End => self.error("code block is not closed", start),
+            _ => self.error("expected a value", start),
+        }
+    }
+
+    fn token(&mut self) -> Result<Token, SyntaxError> {
+        self.skip_whitespace();
+        let start = self.position;
+        let rest = self.rest();
+        let Some(c) = rest.chars().next() else {
+            return Ok(Token::End);
+        };
+
+        let len = identifier_len(rest);
+        if len > 0 {
+            self.position += len;
+            return Ok(Token::Identifier(rest[..len].to_owned()));
+        }
+        if c.is_ascii_digit() {
+            let len = rest
+                .find(|c: char| !c.is_ascii_digit() && c != '.')
+                .unwrap_or(rest.len());
+            self.position += len;
+            return Ok(Token::Number(rest[..len].to_owned()));
+        }
+        if c == '"' {
+            let Some(len) = rest[1..].find('"') else {
+                self.position = self.source.len();
+                return self.error("string is not closed", start);
+            };
+            self.position += len + 2;
+            return Ok(Token::String(rest[1..=len].to_owned()));
+        }
+
+        let (token, len) = match c {
+            '{' => (Token::OpenBrace, 1),
+            '}' => (Token::CloseBrace, 1),
+            '?' => (Token::Question, 1),
+            '=' if rest.starts_with("==") => (Token::Operator(Operator::Equal), 2),
+            '=' => (Token::Assign, 1),
+            '<' if rest.starts_with("<=") => (Token::Operator(Operator::LessOrEqual), 2),
+            '<' => (Token::Operator(Operator::Less), 1),
+            '>' if rest.starts_with(">=") => (Token::Operator(Operator::GreaterOrEqual), 2),
+            '>' => (Token::Operator(Operator::Greater), 1),
+            '+' => (Token::Operator(Operator::Add), 1),
+            '-' => (Token::Operator(Operator::Subtract), 1),
+            '*' => (Token::Operator(Operator::Multiply), 1),
+            '/' => (Token::Operator(Operator::Divide), 1),
+            _ => {
+                self.position += c.len_utf8();
+                return self.error("unexpected character", start);
+            }
+        };
+        self.position += len;
+        Ok(token)
+    }
+}
+
+/// length of the identifier at the start of the string, or 0
+fn identifier_len(s: &str) -> usize {
+    if !s.starts_with(|c: char| c.is_alphabetic() || c == '_') {
+        return 0;
+    }
+    s.find(|c: char| !c.is_alphanumeric() && c != '_')
+        .unwrap_or(s.len())
+}
+
+fn push_text(nodes: &mut Vec<Node>, source: &str, start: usize, end: usize) {
+    if start < end {
+        nodes.push(Node {
+            kind: NodeKind::Text(source[start..end].to_owned()),
+            span: Span { start, end },
+            spacing: Vec::new(),
+        });
+    }
+}
+
+impl Dialogue {
+    pub fn script(&self) -> Result<Script, SyntaxError> {
+        Script::parse(&self.contents)
+    }
+}
+
+impl Ending {
+    pub fn script(&self) -> Result<Script, SyntaxError> {
+        Script::parse(&self.dialogue)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    fn kinds(script: &Script) -> Vec<NodeKind> {
+        script.nodes.iter().map(|node| node.kind.clone()).collect()
+    }
+
+    #[test]
+    fn text_and_effects() {
+        let script = Script::parse("A key! {wvy}What does it open?{wvy}").unwrap();
+        let wvy = NodeKind::Function {
+            name: "wvy".to_string(),
+            arguments: vec![],
+        };
+        assert_eq!(
+            kinds(&script),
+            vec![
+                NodeKind::Text("A key! ".to_string()),
+                wvy.clone(),
+                NodeKind::Text("What does it open?".to_string()),
+                wvy,
+            ]
+        );
+        assert_eq!(script.nodes[1].span, Span { start: 7, end: 12 });
+        assert!(!script.quoted);
+    }
+
+    #[test]
+    fn code() {
+        let script = Script::parse("{a = a + 1}{item \"1\"}{/shk}{br}{a * 2 >= 4}").unwrap();
+        let expected = vec![
+            NodeKind::Assignment {
+                variable: "a".to_string(),
+                value: Expression::Binary {
+                    operator: Operator::Add,
+                    left: Box::new(Expression::Variable("a".to_string())),
+                    right: Box::new(Expression::Number("1".to_string())),
+                },
+            },
+            NodeKind::Function {
+                name: "item".to_string(),
+                arguments: vec![Expression::String("1".to_string())],
+            },
+            NodeKind::Function {
+                name: "/shk".to_string(),
+                arguments: vec![],
+            },
+            NodeKind::Function {
+                name: "br".to_string(),
+                arguments: vec![],
+            },
+            NodeKind::Expression(Expression::Binary {
+                operator: Operator::GreaterOrEqual,
+                left: Box::new(Expression::Binary {
+                    operator: Operator::Multiply,
+                    left: Box::new(Expression::Variable("a".to_string())),
+                    right: Box::new(Expression::Number("2".to_string())),
+                }),
+                right: Box::new(Expression::Number("4".to_string())),
+            }),
+        ];
+        assert_eq!(kinds(&script), expected);
+    }
+
+    #[test]
+    fn conditional() {
+        let source = "\"\"\"\n{\n  - {item \"1\"} > 0 ?\n    thanks!\n  - else ?\n    bring me tea\n}\n\"\"\"";
+        let script = Script::parse(source).unwrap();
+        assert!(script.quoted);
+        let NodeKind::Conditional { branches, closing } = &script.nodes[0].kind else {
+            panic!("expected a conditional");
+        };
+        assert_eq!(branches.len(), 2);
+        assert_eq!(branches[0].indent, "\n  ");
+        assert_eq!(
+            branches[0].condition.as_ref().unwrap().to_string(),
+            "{item \"1\"} > 0"
+        );
+        assert_eq!(
+            branches[0].body,
+            vec![Node {
+                kind: NodeKind::Text("\n    thanks!".to_string()),
+                span: Span { start: 26, end: 38 },
+                spacing: vec![],
+            }]
+        );
+        assert_eq!(branches[1].condition, None);
+        assert_eq!(closing, "\n");
+        assert_eq!(script.to_string(), source);
+    }
+
+    #[test]
+    fn sequence() {
+        let source = "{cycle\n  - hi\n  - hello {say name}\n}";
+        let script = Script::parse(source).unwrap();
+        let NodeKind::Sequence { kind, options, .. } = &script.nodes[0].kind else {
+            panic!("expected a sequence");
+        };
+        assert_eq!(*kind, SequenceKind::Cycle);
+        assert_eq!(options.len(), 2);
+        assert_eq!(options[1].body.len(), 2);
+        assert_eq!(script.to_string(), source);
+
+        let mut functions = 0;
+        script.visit(&mut |node| {
+            if let NodeKind::Function { .. } = node.kind {
+                functions += 1;
+            }
+        });
+        assert_eq!(functions, 1);
+    }
+
+    #[test]
+    fn syntax_errors() {
+        let error = Script::parse("hello {wvy").unwrap_err();
+        assert_eq!(error.message, "code block is not closed");
+        assert_eq!(error.span.start, 6);
+        assert_eq!(
+            Script::parse("{say \"hi}").unwrap_err().message,
+            "string is not closed"
+        );
+        assert_eq!(
+            Script::parse("{\n - a == 1\n}").unwrap_err().message,
+            "expected `?` after condition"
+        );
+        assert_eq!(
+            Script::parse("{sequence hi}").unwrap_err().message,
+            "expected `-`"
+        );
+        assert_eq!(Script::parse("{}").unwrap_err().message, "empty code block");
+        assert_eq!(
+            Script::parse("{a = }").unwrap_err().message,
+            "expected a value"
+        );
+    }
+
+    #[test]
+    fn spacing() {
+        for source in [
+            "{a=a+1}{ say  \"hi\" }{/wvy}{item\t\"1\"}{ a*2>= - 4 }",
+            "{cycle\n  -hi\n  -\t hello {say name}\n}",
+            "{ shuffle\n- a\n- b}",
+            "{\n  -{item \"1\"}>0?\n    thanks!\n  -  else   ?\n    bring me tea\n}",
+        ] {
+            assert_eq!(Script::parse(source).unwrap().to_string(), source);
+        }
+
+        // edited code keeps the spacing of the tokens that are left
+        let mut script = Script::parse("{a=a+1}{say  \"hi\"}").unwrap();
+        script.nodes[0].kind = NodeKind::Assignment {
+            variable: "a".to_string(),
+            value: Expression::Number("2".to_string()),
+        };
+        let NodeKind::Function { arguments, .. } = &mut script.nodes[1].kind else {
+            panic!("expected a function");
+        };
+        arguments.push(Expression::Number("3".to_string()));
+        assert_eq!(script.to_string(), "{a=2}{say  \"hi\" 3}");
+    }
+
+    #[test]
+    fn negative_number() {
+        let script = Script::parse("{-1}{a = - 2}").unwrap();
+        assert_eq!(
+            script.nodes[0].kind,
+            NodeKind::Expression(Expression::Number("-1".to_string()))
+        );
+        assert_eq!(script.to_string(), "{-1}{a = - 2}");
+    }
+
+    #[test]
+    fn round_trip() {
+        for source in [
+            include_str!("test-resources/example.bitsy"),
+            include_str!("test-resources/default-v7.10.bitsy"),
+            include_str!("test-resources/default-v8.14.bitsy"),
+            include_str!("test-resources/arabic.bitsy"),
+        ] {
+            let game = Game::from(source).unwrap();
+            for dialogue in &game.dialogues {
+                let script = dialogue.script().unwrap();
+                assert_eq!(script.to_string(), dialogue.contents);
+            }
+            for ending in &game.endings {
+                assert_eq!(ending.script().unwrap().to_string(), ending.dialogue);
+            }
+        }
+    }
+}
@@ -6,7 +6,7 @@ use core::str::FromStr;
 pub enum Segment {
     Name(String),
     Version(Version),
-    RoomFormat(RoomFormat),
+    Flags(Vec<Flag>),
     Font(Font, Option<String>),
     TextDirection(TextDirection),
     Palette(Palette),
@@ -60,7 +60,7 @@ impl Segments {
             (
                 ! segment.starts_with("# BITSY VERSION ")
                 &&
-                ! segment.starts_with("! ROOM_FORMAT ")
+                ! segment.starts_with("! ")
                 &&
                 ! segment.starts_with("PAL ")
                 &&
@@ -106,10 +106,16 @@ fn parse_segment(segment: String) -> Option<Segment> {
         };
         return Some(segment);
     }
-    if segment.starts_with("! ROOM_FORMAT") {
-        let segment = segment.replace("! ROOM_FORMAT ", "");
-        let room_format = RoomFormat::from(&segment).ok()?;
-        return Some(Segment::RoomFormat(room_format));
+    if segment.starts_with("! ") {
+        let flags = segment
+            .lines()
+            .filter_map(|line| line.strip_prefix("! ")?.split_once(' '))
+            .map(|(name, value)| Flag {
+                name: name.to_string(),
+                value: value.to_string(),
+            })
+            .collect();
+        return Some(Segment::Flags(flags));
     }
     let (first_word, _) = segment.split_once(' ')?;
     match first_word {
@@ -0,0 +1,362 @@
+use crate::*;
+use alloc::collections::{BTreeMap, BTreeSet, VecDeque};
+use alloc::{string::String, vec, vec::Vec};
+
+/// an exit or ending that can only be used while carrying enough of an item.
+///
+/// Bitsy does this kind of locking in dialogue scripts, which are not
+/// interpreted here, so gates have to be described by hand.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ItemGate {
+    pub room_id: String,
+    pub position: Position,
+    pub item_id: String,
+    pub count: u32,
+}
+
+/// an item placed in a room
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoomItem {
+    pub room_id: String,
+    pub item_id: String,
+    pub position: Position,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EndingReach {
+    pub ending_id: String,
+    /// a shortest sequence of moves from the start to the ending,
+    /// or `None` if it can't be reached
+    pub moves: Option<Vec<Direction>>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solution {
+    /// every ending placed in a room
+    pub endings: Vec<EndingReach>,
+    /// items that are placed where the avatar can never walk
+    pub uncollectable: Vec<RoomItem>,
+}
+
+impl Solution {
+    /// whether at least one ending can be reached
+    pub fn is_finishable(&self) -> bool {
+        self.endings.iter().any(|ending| ending.moves.is_some())
+    }
+
+    pub fn moves_to(&self, ending_id: &str) -> Option<&[Direction]> {
+        self.endings
+            .iter()
+            .find(|ending| ending.ending_id == ending_id)
+            .and_then(|ending| ending.moves.as_deref())
+    }
+}
+
+/// where the avatar is and which of the gating items it has picked up
+#[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+struct State {
+    room: usize,
+    x: u8,
+    y: u8,
+    collected: Vec<bool>,
+}
+
+/// searches the game for the shortest way to each ending.
+///
+/// the avatar moves one tile at a time. walls and sprites block it,
+/// stepping onto an item picks it up, stepping onto an exit moves it
+/// to the destination and stepping onto an ending finishes the game.
+pub struct Solver<'a> {
+    game: &'a Game,
+    gates: Vec<ItemGate>,
+}
+
+impl<'a> Solver<'a> {
+    pub fn new(game: &'a Game) -> Self {
+        Self {
+            game,
+            gates: Vec::new(),
+        }
+    }
+
+    pub fn add_gate(&mut self, gate: ItemGate) {
+        self.gates.push(gate);
+    }
+
+    pub fn solve(&self) -> Solution {
+        let game = self.game;
+        let walk_maps: Vec<WalkMap> = game.rooms.iter().map(|r| WalkMap::new(game, r)).collect();
+
+        // only items that open gates need to be part of the search state -
+        // the rest don't change where the avatar can go
+        let gated: Vec<(usize, &Instance)> = game
+            .rooms
+            .iter()
+            .enumerate()
+            .flat_map(|(i, room)| room.items.iter().map(move |item| (i, item)))
+            .filter(|(_, item)| self.gates.iter().any(|gate| gate.item_id == item.id))
+            .collect();
+        let mut starting_inventory: BTreeMap<&str, u32> = BTreeMap::new();
+        if let Some(avatar) = game.get_avatar() {
+            for (id, count) in avatar.inventory() {
+                *starting_inventory.entry(id).or_default() += count;
+            }
+        }
+
+        let mut endings: Vec<EndingReach> = Vec::new();
+        for room in &game.rooms {
+            for ending in &room.endings {
+                if !endings.iter().any(|e| e.ending_id == ending.id) {
+                    endings.push(EndingReach {
+                        ending_id: ending.id.clone(),
+                        moves: None,
+                    });
+                }
+            }
+        }
+        let mut visited_tiles: BTreeSet<(usize, u8, u8)> = BTreeSet::new();
+
+        let start = game.get_avatar().and_then(|avatar| {
+            let room = game
+                .rooms
+                .iter()
+                .position(|r| Some(&r.id) == avatar.room_id.as_ref())?;
+            let position = avatar.position.filter(|p| p.in_bounds())?;
+            Some(State {
+                room,
+                x: position.x,
+                y: position.y,
+                collected: vec![false; gated.len()],
+            })
+        });
+
+        // every state with the index of the state it was reached from and the move
+        let mut states: Vec<(State, usize, Option<Direction>)> = Vec::new();
+        let mut seen: BTreeSet<State> = BTreeSet::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        if let Some(start) = start {
+            seen.insert(start.clone());
+            states.push((start, 0, None));
+            queue.push_back(0);
+        }
+
+        let moves_to = |states: &[(State, usize, Option<Direction>)], mut index: usize| {
+            let mut moves = Vec::new();
+            while let (_, previous, Some(direction)) = &states[index] {
+                moves.push(*direction);
+                index = *previous;
+            }
+            moves.reverse();
+            moves
+        };
+
+        while let Some(index) = queue.pop_front() {
+            let state = states[index].0.clone();
+            visited_tiles.insert((state.room, state.x, state.y));
+            let room = &game.rooms[state.room];
+            let here = Position {
+                x: state.x,
+                y: state.y,
+            };
+
+            for direction in Direction::ALL {
+                let Some(next) = here.step(direction) else {
+                    continue;
+                };
+                if !walk_maps[state.room].is_walkable(next) {
+                    continue;
+                }
+                let locked = self.gates.iter().any(|gate| {
+                    gate.room_id == room.id
+                        && gate.position == next
+                        && self.count(&gate.item_id, &starting_inventory, &gated, &state)
+                            < gate.count
+                });
+                if locked {
+                    continue;
+                }
+
+                if let Some(ending) = room.endings.iter().find(|e| e.position == next) {
+                    let reach = endings.iter_mut().find(|e| e.ending_id == ending.id);
+                    if let Some(reach) = reach.filter(|r| r.moves.is_none()) {
+                        let mut moves = moves_to(&states, index);
+                        moves.push(direction);
+                        reach.moves = Some(moves);
+                    }
+                    continue;
+                }
+
+                let mut next_state = State {
+                    room: state.room,
+                    x: next.x,
+                    y: next.y,
+                    collected: state.collected.clone(),
+                };
+                if let Some(exit) = room.exits.iter().find(|e| e.position == next) {
+                    let destination = game.rooms.iter().position(|r| r.id == exit.exit.room_id);
+                    let (Some(destination), true) = (destination, exit.exit.position.in_bounds())
+                    else {
+                        continue;
+                    };
+                    // the avatar passes over the exit tile
+                    visited_tiles.insert((state.room, next.x, next.y));
+                    next_state.room = destination;
+                    next_state.x = exit.exit.position.x;
+                    next_state.y = exit.exit.position.y;
+                }
+                for (i, (room, item)) in gated.iter().enumerate() {
+                    if *room == next_state.room
+                        && item.position.x == next_state.x
+                        && item.position.y == next_state.y
+                    {
+                        next_state.collected[i] = true;
+                    }
+                }
+
+                if seen.insert(next_state.clone()) {
+                    states.push((next_state, index, Some(direction)));
+                    queue.push_back(states.len() - 1);
+                }
+            }
+        }
+
+        let mut uncollectable = Vec::new();
+        for (i, room) in game.rooms.iter().enumerate() {
+            for item in &room.items {
+                if !visited_tiles.contains(&(i, item.position.x, item.position.y)) {
+                    uncollectable.push(RoomItem {
+                        room_id: room.id.clone(),
+                        item_id: item.id.clone(),
+                        position: item.position,
+                    });
+                }
+            }
+        }
+
+        Solution {
+            endings,
+            uncollectable,
+        }
+    }
+
+    /// how many of the item the avatar carries in the state
+    fn count(
+        &self,
+        item_id: &str,
+        starting_inventory: &BTreeMap<&str, u32>,
+        gated: &[(usize, &Instance)],
+        state: &State,
+    ) -> u32 {
+        let collected = gated
+            .iter()
+            .zip(&state.collected)
+            .filter(|((_, item), collected)| **collected && item.id == item_id)
+            .count() as u32;
+        starting_inventory.get(item_id).copied().unwrap_or(0) + collected
+    }
+}
+
+impl Game {
+    /// checks which endings can be reached and which items can be collected,
+    /// without any item gates
+    pub fn solve(&self) -> Solution {
+        Solver::new(self).solve()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+
+    /// the default room with a wall ring around 1,1 .. 14,14,
+    /// an ending at 10,4 and a key at 4,10
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game.rooms[0].endings.push(Instance {
+            position: Position { x: 10, y: 4 },
+            id: "0".to_string(),
+        });
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 4, y: 10 },
+            id: "1".to_string(),
+        });
+        game
+    }
+
+    #[test]
+    fn solve() {
+        let solution = game().solve();
+        assert!(solution.is_finishable());
+        assert_eq!(solution.moves_to("0").unwrap().len(), 6);
+        assert_eq!(solution.uncollectable, vec![]);
+    }
+
+    #[test]
+    fn uncollectable_items() {
+        let mut game = game();
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 0, y: 0 },
+            id: "0".to_string(),
+        });
+        let solution = game.solve();
+        assert_eq!(
+            solution.uncollectable,
+            vec![RoomItem {
+                room_id: "0".to_string(),
+                item_id: "0".to_string(),
+                position: Position { x: 0, y: 0 },
+            }]
+        );
+    }
+
+    #[test]
+    fn unreachable_ending() {
+        let mut game = game();
+        game.rooms[0].endings[0].position = Position { x: 15, y: 15 };
+        let solution = game.solve();
+        assert!(!solution.is_finishable());
+        assert_eq!(solution.moves_to("0"), None);
+    }
+
+    #[test]
+    fn item_gate() {
+        let game = game();
+        let mut solver = Solver::new(&game);
+        solver.add_gate(ItemGate {
+            room_id: "0".to_string(),
+            position: Position { x: 10, y: 4 },
+            item_id: "1".to_string(),
+            count: 1,
+        });
+        // down 6 and back up 6 to fetch the key, then 6 right to the ending
+        assert_eq!(solver.solve().moves_to("0").unwrap().len(), 18);
+
+        let mut game = game.clone();
+        game.rooms[0].items.clear();
+        let mut solver = Solver::new(&game);
+        solver.add_gate(ItemGate {
+            room_id: "0".to_string(),
+            position: Position { x: 10, y: 4 },
+            item_id: "1".to_string(),
+            count: 1,
+        });
+        assert!(!solver.solve().is_finishable());
+    }
+
+    #[test]
+    fn solve_through_exits() {
+        let mut game = Game::from(include_str!("test-resources/example.bitsy")).unwrap();
+        // no exit leads to the room with the ending
+        assert!(!game.solve().is_finishable());
+
+        let room = game.rooms.iter_mut().find(|room| room.id == "3").unwrap();
+        room.exits[1].exit.room_id = "a".to_string();
+        room.exits[1].exit.position = Position { x: 3, y: 3 };
+        let solution = game.solve();
+        assert!(solution.is_finishable());
+        assert!(!solution.moves_to("undefined").unwrap().is_empty());
+    }
+}
@@ -17,6 +17,18 @@ pub struct Sprite {
 }
 
 impl Sprite {
+    /// the starting inventory as item IDs and counts.
+    /// a missing or unreadable count is treated as 1.
+    pub fn inventory(&self) -> Vec<(&str, u32)> {
+        self.items
+            .iter()
+            .map(|line| match line.split_once(' ') {
+                Some((id, count)) => (id, count.trim().parse().unwrap_or(1)),
+                None => (line.as_str(), 1),
+            })
+            .collect()
+    }
+
     fn name_line(&self) -> String {
         optional_data_line("NAME", self.name.as_ref())
     }
@@ -163,4 +175,14 @@ mod test {
             include_str!("test-resources/sprite")
         );
     }
+
+    #[test]
+    fn sprite_inventory() {
+        let mut sprite = mock::sprite();
+        sprite.items = alloc::vec!["0 2".to_string(), "1".to_string(), "2 x".to_string()];
+        assert_eq!(
+            sprite.inventory(),
+            alloc::vec![("0", 2), ("1", 1), ("2", 1)]
+        );
+    }
 }
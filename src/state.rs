@@ -0,0 +1,397 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::collections::BTreeMap;
+use alloc::{string::String, vec, vec::Vec};
+
+/// something that happened as a result of a move
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Event {
+    /// a wall or the edge of the room is in the way
+    Blocked,
+    /// the avatar bumped into a sprite
+    TalkToSprite {
+        sprite_id: String,
+    },
+    /// a dialogue should be shown. the script is not run.
+    ShowDialogue {
+        dialogue_id: String,
+    },
+    PickUpItem {
+        item_id: String,
+    },
+    Exit {
+        from_room_id: String,
+        to_room_id: String,
+        position: Position,
+        transition: Transition,
+    },
+    Ending {
+        ending_id: String,
+    },
+}
+
+/// a game being played, without any graphics or dialogue scripts.
+///
+/// the avatar starts where sprite `A` is placed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameState<'a> {
+    pub(crate) game: &'a Game,
+    pub(crate) room_id: String,
+    pub(crate) position: Position,
+    pub(crate) inventory: BTreeMap<String, u32>,
+    /// items that haven't been picked up yet, by room ID
+    pub(crate) room_items: BTreeMap<String, Vec<Instance>>,
+    /// variable values by ID
+    pub(crate) variables: BTreeMap<String, VariableValue>,
+    /// how far each sequence, cycle or shuffle in a dialogue has advanced,
+    /// in the order they appear in the dialogue
+    pub(crate) sequences: BTreeMap<String, Vec<u32>>,
+    pub(crate) ending_id: Option<String>,
+}
+
+impl<'a> GameState<'a> {
+    pub fn new(game: &'a Game) -> Result<Self, crate::Error> {
+        let avatar = game.get_avatar().ok_or(crate::Error::Game {
+            missing: NotFound::Avatar,
+        })?;
+        let (Some(room_id), Some(position)) = (&avatar.room_id, avatar.position) else {
+            return Err(crate::Error::Game {
+                missing: NotFound::Avatar,
+            });
+        };
+        if game.get_room(room_id).is_none() {
+            return Err(crate::Error::Game {
+                missing: NotFound::Room,
+            });
+        }
+        if !position.in_bounds() {
+            return Err(crate::Error::Position);
+        }
+        let mut inventory = BTreeMap::new();
+        for (id, count) in avatar.inventory() {
+            *inventory.entry(id.to_owned()).or_default() += count;
+        }
+        Ok(Self {
+            game,
+            room_id: room_id.clone(),
+            position,
+            inventory,
+            room_items: game
+                .rooms
+                .iter()
+                .map(|room| (room.id.clone(), room.items.clone()))
+                .collect(),
+            variables: game
+                .variables
+                .iter()
+                .map(|variable| (variable.id.clone(), variable.initial_value.clone()))
+                .collect(),
+            sequences: BTreeMap::new(),
+            ending_id: None,
+        })
+    }
+
+    pub fn game(&self) -> &'a Game {
+        self.game
+    }
+
+    pub fn room_id(&self) -> &str {
+        &self.room_id
+    }
+
+    pub fn room(&self) -> &'a Room {
+        // the room is checked when the state is created and when exits are used
+        self.game.get_room(&self.room_id).unwrap()
+    }
+
+    pub fn position(&self) -> Position {
+        self.position
+    }
+
+    /// item IDs and how many of each the avatar is carrying
+    pub fn inventory(&self) -> &BTreeMap<String, u32> {
+        &self.inventory
+    }
+
+    pub fn item_count(&self, item_id: &str) -> u32 {
+        self.inventory.get(item_id).copied().unwrap_or(0)
+    }
+
+    /// items in the room that haven't been picked up yet
+    pub fn room_items(&self, room_id: &str) -> &[Instance] {
+        self.room_items.get(room_id).map_or(&[], |items| items)
+    }
+
+    pub fn variables(&self) -> &BTreeMap<String, VariableValue> {
+        &self.variables
+    }
+
+    pub fn variable(&self, id: &str) -> Option<&VariableValue> {
+        self.variables.get(id)
+    }
+
+    /// dialogue scripts are not run here, so this is for whatever runs them
+    pub fn set_variable(&mut self, id: &str, value: VariableValue) {
+        self.variables.insert(id.to_owned(), value);
+    }
+
+    pub fn sequences(&self) -> &BTreeMap<String, Vec<u32>> {
+        &self.sequences
+    }
+
+    /// the counter of the nth sequence, cycle or shuffle in the dialogue
+    pub fn sequence_counter(&self, dialogue_id: &str, index: usize) -> u32 {
+        self.sequences
+            .get(dialogue_id)
+            .and_then(|counters| counters.get(index))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    pub fn set_sequence_counter(&mut self, dialogue_id: &str, index: usize, value: u32) {
+        let counters = self.sequences.entry(dialogue_id.to_owned()).or_default();
+        if counters.len() <= index {
+            counters.resize(index + 1, 0);
+        }
+        counters[index] = value;
+    }
+
+    /// the ending that was reached, if the game is over
+    pub fn ending_id(&self) -> Option<&str> {
+        self.ending_id.as_deref()
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.ending_id.is_some()
+    }
+
+    /// tries to move the avatar one tile in the direction.
+    /// nothing happens once the game is over.
+    pub fn step(&mut self, direction: Direction) -> Vec<Event> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        let Some(next) = self.position.step(direction) else {
+            return vec![Event::Blocked];
+        };
+        let game = self.game;
+        let room = self.room();
+
+        let sprite = game.sprites.iter().find(|sprite| {
+            sprite.id != "A"
+                && sprite.room_id.as_ref() == Some(&room.id)
+                && sprite.position == Some(next)
+        });
+        if let Some(sprite) = sprite {
+            let mut events = vec![Event::TalkToSprite {
+                sprite_id: sprite.id.clone(),
+            }];
+            if let Some(id) = game.sprite_dialogue_id(sprite) {
+                events.push(Event::ShowDialogue {
+                    dialogue_id: id.to_owned(),
+                });
+            }
+            return events;
+        }
+        if game.is_wall(room, next) {
+            return vec![Event::Blocked];
+        }
+
+        self.position = next;
+        let mut events = Vec::new();
+
+        let items = self.room_items.entry(room.id.clone()).or_default();
+        if let Some(index) = items.iter().position(|item| item.position == next) {
+            let item_id = items.remove(index).id;
+            *self.inventory.entry(item_id.clone()).or_default() += 1;
+            let dialogue_id = game.get_item(&item_id).and_then(|i| i.dialogue_id.clone());
+            events.push(Event::PickUpItem { item_id });
+            if let Some(dialogue_id) = dialogue_id {
+                events.push(Event::ShowDialogue { dialogue_id });
+            }
+        }
+
+        if let Some(ending) = room.endings.iter().find(|e| e.position == next) {
+            self.ending_id = Some(ending.id.clone());
+            events.push(Event::Ending {
+                ending_id: ending.id.clone(),
+            });
+            // since Bitsy 8, endings are dialogues
+            if game.dialogues.iter().any(|d| d.id == ending.id) {
+                events.push(Event::ShowDialogue {
+                    dialogue_id: ending.id.clone(),
+                });
+            }
+            return events;
+        }
+
+        let exit = room.exits.iter().find(|e| e.position == next);
+        let exit = exit
+            .filter(|e| e.exit.position.in_bounds() && game.get_room(&e.exit.room_id).is_some());
+        if let Some(exit) = exit {
+            events.push(Event::Exit {
+                from_room_id: room.id.clone(),
+                to_room_id: exit.exit.room_id.clone(),
+                position: exit.exit.position,
+                transition: exit.transition.unwrap_or(exit.exit.effect),
+            });
+            if let Some(dialogue_id) = &exit.dialogue_id {
+                events.push(Event::ShowDialogue {
+                    dialogue_id: dialogue_id.clone(),
+                });
+            }
+            self.room_id = exit.exit.room_id.clone();
+            self.position = exit.exit.position;
+        }
+
+        events
+    }
+}
+
+impl Game {
+    /// starts playing the game headlessly
+    pub fn play(&self) -> Result<GameState<'_>, crate::Error> {
+        GameState::new(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+
+    /// the default room with a wall ring around 1,1 .. 14,14
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game
+    }
+
+    #[test]
+    fn walls_block() {
+        let game = game();
+        let mut state = game.play().unwrap();
+        assert_eq!(state.position(), Position { x: 4, y: 4 });
+        for _ in 0..2 {
+            assert_eq!(state.step(Direction::Up), vec![]);
+        }
+        assert_eq!(state.step(Direction::Up), vec![Event::Blocked]);
+        assert_eq!(state.position(), Position { x: 4, y: 2 });
+    }
+
+    #[test]
+    fn pick_up_item() {
+        let mut game = game();
+        game.rooms[0].items.push(Instance {
+            position: Position { x: 5, y: 4 },
+            id: "0".to_string(),
+        });
+        let mut state = game.play().unwrap();
+        let expected = vec![
+            Event::PickUpItem {
+                item_id: "0".to_string(),
+            },
+            Event::ShowDialogue {
+                dialogue_id: "1".to_string(),
+            },
+        ];
+        assert_eq!(state.step(Direction::Right), expected);
+        assert_eq!(state.item_count("0"), 1);
+        assert_eq!(state.room_items("0"), &[]);
+        state.step(Direction::Left);
+        assert_eq!(state.step(Direction::Right), vec![]);
+        assert_eq!(state.item_count("0"), 1);
+    }
+
+    #[test]
+    fn talk_to_sprite() {
+        let mut game = game();
+        game.sprites[1].position = Some(Position { x: 4, y: 5 });
+        let mut state = game.play().unwrap();
+        let expected = vec![
+            Event::TalkToSprite {
+                sprite_id: "a".to_string(),
+            },
+            Event::ShowDialogue {
+                dialogue_id: "0".to_string(),
+            },
+        ];
+        assert_eq!(state.step(Direction::Down), expected);
+        assert_eq!(state.position(), Position { x: 4, y: 4 });
+    }
+
+    #[test]
+    fn exit_and_ending() {
+        let mut game = game();
+        let mut room = mock::room();
+        room.id = "1".to_string();
+        room.exits.clear();
+        room.endings = vec![Instance {
+            position: Position { x: 6, y: 6 },
+            id: "end".to_string(),
+        }];
+        room.items.clear();
+        game.rooms.push(room);
+        game.rooms[0].exits.push(ExitInstance {
+            position: Position { x: 4, y: 3 },
+            exit: Exit {
+                room_id: "1".to_string(),
+                position: Position { x: 6, y: 5 },
+                effect: Transition::None,
+            },
+            transition: Some(Transition::FadeToBlack),
+            dialogue_id: None,
+        });
+
+        let mut state = game.play().unwrap();
+        let expected = vec![Event::Exit {
+            from_room_id: "0".to_string(),
+            to_room_id: "1".to_string(),
+            position: Position { x: 6, y: 5 },
+            transition: Transition::FadeToBlack,
+        }];
+        assert_eq!(state.step(Direction::Up), expected);
+        assert_eq!(state.room_id(), "1");
+
+        let expected = vec![Event::Ending {
+            ending_id: "end".to_string(),
+        }];
+        assert_eq!(state.step(Direction::Down), expected);
+        assert_eq!(state.ending_id(), Some("end"));
+        assert_eq!(state.step(Direction::Down), vec![]);
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = game();
+        game.rooms[0].exits.push(ExitInstance {
+            position: Position { x: 4, y: 3 },
+            exit: Exit {
+                room_id: "0".to_string(),
+                position: Position { x: 16, y: 5 },
+                effect: Transition::None,
+            },
+            transition: None,
+            dialogue_id: None,
+        });
+        let mut state = game.play().unwrap();
+        assert_eq!(state.step(Direction::Up), vec![]);
+        assert_eq!(state.position(), Position { x: 4, y: 3 });
+
+        game.sprites[0].position = Some(Position { x: 255, y: 4 });
+        assert_eq!(game.play(), Err(crate::Error::Position));
+    }
+
+    #[test]
+    fn no_avatar() {
+        let mut game = game();
+        game.sprites.remove(0);
+        assert_eq!(
+            game.play(),
+            Err(crate::Error::Game {
+                missing: NotFound::Avatar
+            })
+        );
+    }
+}
@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod test {
-    use crate::game::Game;
+    use crate::game::{Game, Version};
     use alloc::string::ToString;
 
     /// bitsy-parser will parse these games correctly
@@ -60,6 +60,37 @@ mod test {
         } else {
             assert_eq!(actual, expected, "output does not match input");
         }
+        migrate(&game);
+    }
+
+    /// migrating to Bitsy 8 keeps the layout of walls and sprite dialogue
+    fn migrate(game: &Game) {
+        let mut migrated = game.clone();
+        migrated
+            .migrate_to(Version {
+                major: 8,
+                minor: 14,
+            })
+            .expect("failed to migrate game");
+        let migrated = Game::from(&migrated.to_string()).expect("failed to parse migrated game");
+        for (room, original) in migrated.rooms.iter().zip(&game.rooms) {
+            for index in 0..original.tiles.len().min(256) {
+                let position = crate::Position {
+                    x: (index % 16) as u8,
+                    y: (index / 16) as u8,
+                };
+                assert_eq!(
+                    migrated.is_wall(room, position),
+                    game.is_wall(original, position)
+                );
+            }
+        }
+        for (sprite, original) in migrated.sprites.iter().zip(&game.sprites) {
+            assert_eq!(
+                migrated.sprite_dialogue_id(sprite),
+                game.sprite_dialogue_id(original)
+            );
+        }
     }
 
     #[test]
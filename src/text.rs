@@ -25,6 +25,7 @@ impl Font {
 
     pub(crate) fn to_string(&self) -> Result<String, crate::Error> {
         match &self {
+            Font::AsciiSmall => Ok("ascii_small".to_string()),
             Font::UnicodeEuropeanSmall => Ok("unicode_european_small".to_string()),
             Font::UnicodeEuropeanLarge => Ok("unicode_european_large".to_string()),
             Font::UnicodeAsian => Ok("unicode_asian".to_string()),
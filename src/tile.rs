@@ -171,6 +171,7 @@ mod test {
             name: Some("concrete 1".to_string()),
             wall: Some(true),
             animation_frames: vec![Image {
+                dimension: 8,
                 pixels: vec![1; 64],
             }],
             colour_id: None,
@@ -0,0 +1,449 @@
+use crate::*;
+use alloc::borrow::ToOwned;
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// kinds of things a diagnostic can point at
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum EntityKind {
+    Game,
+    Palette,
+    Room,
+    Tile,
+    Sprite,
+    Item,
+    Dialogue,
+    Ending,
+    Variable,
+}
+
+impl EntityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EntityKind::Game => "game",
+            EntityKind::Palette => "palette",
+            EntityKind::Room => "room",
+            EntityKind::Tile => "tile",
+            EntityKind::Sprite => "sprite",
+            EntityKind::Item => "item",
+            EntityKind::Dialogue => "dialogue",
+            EntityKind::Ending => "ending",
+            EntityKind::Variable => "variable",
+        }
+    }
+}
+
+impl fmt::Display for EntityKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Severity {
+    /// the game will run but probably not as intended
+    Warning,
+    /// the game data is broken
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub entity: EntityKind,
+    /// ID of the entity - empty for the game itself
+    pub id: String,
+    /// the property with the problem, e.g. `palette_id`
+    pub field: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.id.is_empty() {
+            write!(
+                f,
+                "{}: {} {}: {}",
+                self.severity, self.entity, self.field, self.message
+            )
+        } else {
+            write!(
+                f,
+                "{}: {} {} {}: {}",
+                self.severity, self.entity, self.id, self.field, self.message
+            )
+        }
+    }
+}
+
+impl Diagnostic {
+    pub(crate) fn warning(
+        entity: EntityKind,
+        id: &str,
+        field: &'static str,
+        message: String,
+    ) -> Self {
+        Self {
+            severity: Severity::Warning,
+            entity,
+            id: id.to_owned(),
+            field,
+            message,
+        }
+    }
+}
+
+/// collects diagnostics for a single entity
+struct Report<'a> {
+    diagnostics: &'a mut Vec<Diagnostic>,
+    entity: EntityKind,
+    id: &'a str,
+}
+
+impl Report<'_> {
+    fn push(&mut self, severity: Severity, field: &'static str, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            entity: self.entity,
+            id: self.id.to_owned(),
+            field,
+            message,
+        });
+    }
+
+    fn error(&mut self, field: &'static str, message: String) {
+        self.push(Severity::Error, field, message);
+    }
+
+    fn warning(&mut self, field: &'static str, message: String) {
+        self.push(Severity::Warning, field, message);
+    }
+
+    fn position(&mut self, field: &'static str, position: &Position) {
+        if !position.in_bounds() {
+            self.error(field, format!("position {} is out of bounds", position));
+        }
+    }
+
+    /// every frame needs a full set of pixels, at the size of the first frame
+    fn frames(&mut self, frames: &[Image]) {
+        let Some(first) = frames.first() else {
+            return;
+        };
+        for (i, frame) in frames.iter().enumerate() {
+            let expected = usize::from(frame.dimension).pow(2);
+            if frame.pixels.len() != expected {
+                self.error(
+                    "animation_frames",
+                    format!(
+                        "frame {} has {} pixels, expected {}",
+                        i,
+                        frame.pixels.len(),
+                        expected
+                    ),
+                );
+            } else if frame.dimension != first.dimension {
+                self.error(
+                    "animation_frames",
+                    format!(
+                        "frame {} is {}x{}, but frame 0 is {}x{}",
+                        i, frame.dimension, frame.dimension, first.dimension, first.dimension
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn duplicate_ids(diagnostics: &mut Vec<Diagnostic>, entity: EntityKind, ids: &[String]) {
+    for (i, id) in ids.iter().enumerate() {
+        // only report the first repetition of each ID
+        if ids[..i].iter().filter(|other| *other == id).count() == 1 {
+            diagnostics.push(Diagnostic {
+                severity: Severity::Error,
+                entity,
+                id: id.clone(),
+                field: "id",
+                message: format!("{} ID {} is used more than once", entity, id),
+            });
+        }
+    }
+}
+
+impl Game {
+    /// checks that all references between entities resolve
+    /// and that all positions are inside the room.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+
+        let mut game = Report {
+            diagnostics: &mut diagnostics,
+            entity: EntityKind::Game,
+            id: "",
+        };
+        match self.get_avatar() {
+            None => game.error("sprites", "avatar sprite `A` not found".to_owned()),
+            Some(avatar) if avatar.room_id.is_none() || avatar.position.is_none() => {
+                game.error("sprites", "avatar has no starting position".to_owned())
+            }
+            Some(_) => {}
+        }
+        if let Some(version) = self.version {
+            for feature in self.used_features() {
+                if !version.supports(feature) {
+                    game.warning("version", feature.unsupported_by(version));
+                }
+            }
+        }
+
+        duplicate_ids(&mut diagnostics, EntityKind::Palette, &self.palette_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Room, &self.room_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Tile, &self.tile_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Sprite, &self.sprite_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Item, &self.item_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Dialogue, &self.dialogue_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Ending, &self.ending_ids());
+        duplicate_ids(&mut diagnostics, EntityKind::Variable, &self.variable_ids());
+
+        for palette in &self.palettes {
+            if palette.colours.len() < 3 {
+                Report {
+                    diagnostics: &mut diagnostics,
+                    entity: EntityKind::Palette,
+                    id: &palette.id,
+                }
+                .warning(
+                    "colours",
+                    format!("palette has {} colours, expected 3", palette.colours.len()),
+                );
+            }
+        }
+
+        for room in &self.rooms {
+            self.validate_room(room, &mut diagnostics);
+        }
+
+        for tile in &self.tiles {
+            Report {
+                diagnostics: &mut diagnostics,
+                entity: EntityKind::Tile,
+                id: &tile.id,
+            }
+            .frames(&tile.animation_frames);
+        }
+
+        for sprite in &self.sprites {
+            let mut report = Report {
+                diagnostics: &mut diagnostics,
+                entity: EntityKind::Sprite,
+                id: &sprite.id,
+            };
+            report.frames(&sprite.animation_frames);
+            if let Some(room_id) = &sprite.room_id
+                && self.get_room(room_id).is_none()
+            {
+                report.error("room_id", format!("room {} not found", room_id));
+            }
+            if let Some(position) = &sprite.position {
+                report.position("position", position);
+            }
+            if let Some(id) = &sprite.dialogue_id
+                && !self.dialogues.iter().any(|d| &d.id == id)
+            {
+                report.error("dialogue_id", format!("dialogue {} not found", id));
+            }
+            for line in &sprite.items {
+                let (id, _count) = line.split_once(' ').unwrap_or((line, ""));
+                if self.get_item(id).is_none() {
+                    report.error("items", format!("item {} not found", id));
+                }
+            }
+        }
+
+        for item in &self.items {
+            let mut report = Report {
+                diagnostics: &mut diagnostics,
+                entity: EntityKind::Item,
+                id: &item.id,
+            };
+            report.frames(&item.animation_frames);
+            if let Some(id) = &item.dialogue_id
+                && !self.dialogues.iter().any(|d| &d.id == id)
+            {
+                report.error("dialogue_id", format!("dialogue {} not found", id));
+            }
+        }
+
+        diagnostics
+    }
+
+    fn validate_room(&self, room: &Room, diagnostics: &mut Vec<Diagnostic>) {
+        let mut report = Report {
+            diagnostics,
+            entity: EntityKind::Room,
+            id: &room.id,
+        };
+
+        if let Some(id) = &room.palette_id
+            && self.get_palette(id).is_none()
+        {
+            report.error("palette_id", format!("palette {} not found", id));
+        }
+
+        if room.tiles.len() != 256 {
+            report.warning(
+                "tiles",
+                format!("room has {} tiles, expected 256", room.tiles.len()),
+            );
+        }
+        let mut missing: Vec<&str> = Vec::new();
+        for id in &room.tiles {
+            if id != "0" && self.get_tile(id).is_none() && !missing.contains(&id.as_str()) {
+                missing.push(id);
+                report.error("tiles", format!("tile {} not found", id));
+            }
+        }
+        for id in room.walls.iter().flatten() {
+            if self.get_tile(id).is_none() {
+                report.warning("walls", format!("wall tile {} not found", id));
+            }
+        }
+
+        for instance in &room.items {
+            if self.get_item(&instance.id).is_none() {
+                report.error("items", format!("item {} not found", instance.id));
+            }
+            report.position("items", &instance.position);
+        }
+
+        for instance in &room.endings {
+            // since Bitsy 8, endings are dialogues
+            let found = self.endings.iter().any(|e| e.id == instance.id)
+                || self.dialogues.iter().any(|d| d.id == instance.id);
+            if !found {
+                report.error("endings", format!("ending {} not found", instance.id));
+            }
+            report.position("endings", &instance.position);
+        }
+
+        for exit in &room.exits {
+            report.position("exits", &exit.position);
+            let destination = &exit.exit;
+            if self.get_room(&destination.room_id).is_none() {
+                report.error(
+                    "exits",
+                    format!("destination room {} not found", destination.room_id),
+                );
+            }
+            if !destination.position.in_bounds() {
+                report.error(
+                    "exits",
+                    format!(
+                        "destination position {} is out of bounds",
+                        destination.position
+                    ),
+                );
+            }
+            if let Some(id) = &exit.dialogue_id
+                && !self.dialogues.iter().any(|d| &d.id == id)
+            {
+                report.error("exits", format!("dialogue {} not found", id));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn validate_default_game() {
+        assert_eq!(mock::game_default().validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_broken_references() {
+        let mut game = mock::game_default();
+        game.rooms[0].palette_id = Some("5".to_string());
+        game.rooms[0].tiles[0] = "x".to_string();
+        game.rooms[0].exits.push(mock::room().exits[0].clone());
+        game.sprites[1].dialogue_id = Some("9".to_string());
+        game.sprites[1].position = Some(Position { x: 16, y: 0 });
+
+        let output: Vec<String> = game.validate().iter().map(|d| d.to_string()).collect();
+        let expected = vec![
+            "error: room 0 palette_id: palette 5 not found",
+            "error: room 0 tiles: tile x not found",
+            "error: room 0 exits: destination room 3 not found",
+            "error: sprite a position: position 16,0 is out of bounds",
+            "error: sprite a dialogue_id: dialogue 9 not found",
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn validate_version_features() {
+        let mut game = mock::game_default();
+        game.version = Some(Version { major: 6, minor: 5 });
+        game.text_direction = TextDirection::RightToLeft;
+        let output: Vec<String> = game.validate().iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            output,
+            vec!["warning: game version: Bitsy 6.5 doesn't support text direction"]
+        );
+    }
+
+    #[test]
+    fn validate_frame_sizes() {
+        let mut game = mock::game_default();
+        game.sprites[0].animation_frames.push(Image {
+            dimension: 16,
+            pixels: vec![0; 256],
+        });
+        game.items[0].animation_frames[0].pixels.pop();
+
+        let output: Vec<String> = game.validate().iter().map(|d| d.to_string()).collect();
+        let expected = vec![
+            "warning: game version: Bitsy 7.10 doesn't support HD images",
+            "error: sprite A animation_frames: frame 1 is 16x16, but frame 0 is 8x8",
+            "error: item 0 animation_frames: frame 0 has 63 pixels, expected 64",
+        ];
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn validate_avatar() {
+        let mut game = mock::game_default();
+        game.sprites.remove(0);
+        let diagnostics = game.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].entity, EntityKind::Game);
+    }
+
+    #[test]
+    fn validate_duplicate_ids() {
+        let mut game = mock::game_default();
+        game.tiles.push(mock::tile_default());
+        game.tiles.push(mock::tile_default());
+        let diagnostics = game.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "error: tile a id: tile ID a is used more than once"
+        );
+    }
+}
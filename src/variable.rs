@@ -1,10 +1,121 @@
 use alloc::string::ToString;
 use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+
+/// a variable value, typed the way Bitsy reads it:
+/// `true` and `false` are booleans, anything that reads as a number is a number,
+/// and everything else is a string.
+#[derive(Clone, Debug)]
+pub enum VariableValue {
+    /// the original text is kept so e.g. `1.50` isn't written back as `1.5`
+    Number {
+        value: f64,
+        text: String,
+    },
+    Boolean(bool),
+    String(String),
+}
+
+impl VariableValue {
+    pub fn parse(text: &str) -> VariableValue {
+        match text {
+            "true" => VariableValue::Boolean(true),
+            "false" => VariableValue::Boolean(false),
+            _ => {
+                let numeric = text
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || matches!(c, '.' | '-' | '+' | 'e' | 'E'));
+                match text.parse::<f64>() {
+                    Ok(value) if numeric && value.is_finite() => VariableValue::Number {
+                        value,
+                        text: text.to_string(),
+                    },
+                    _ => VariableValue::String(text.to_string()),
+                }
+            }
+        }
+    }
+
+    /// booleans count as 1 and 0, strings aren't numbers
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            VariableValue::Number { value, .. } => Some(*value),
+            VariableValue::Boolean(value) => Some(if *value { 1.0 } else { 0.0 }),
+            VariableValue::String(_) => None,
+        }
+    }
+
+    /// whether the value counts as true in a condition.
+    /// zero and empty strings are false.
+    pub fn as_bool(&self) -> bool {
+        match self {
+            VariableValue::Number { value, .. } => *value != 0.0,
+            VariableValue::Boolean(value) => *value,
+            VariableValue::String(string) => !string.is_empty(),
+        }
+    }
+}
+
+impl PartialEq for VariableValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                VariableValue::Number { value, text },
+                VariableValue::Number {
+                    value: other_value,
+                    text: other_text,
+                },
+            ) => value.to_bits() == other_value.to_bits() && text == other_text,
+            (VariableValue::Boolean(a), VariableValue::Boolean(b)) => a == b,
+            (VariableValue::String(a), VariableValue::String(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for VariableValue {}
+
+impl Default for VariableValue {
+    fn default() -> Self {
+        VariableValue::String(String::new())
+    }
+}
+
+impl From<f64> for VariableValue {
+    fn from(value: f64) -> Self {
+        VariableValue::Number {
+            value,
+            text: format!("{}", value),
+        }
+    }
+}
+
+impl From<bool> for VariableValue {
+    fn from(value: bool) -> Self {
+        VariableValue::Boolean(value)
+    }
+}
+
+impl From<&str> for VariableValue {
+    fn from(value: &str) -> Self {
+        VariableValue::parse(value)
+    }
+}
+
+impl fmt::Display for VariableValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VariableValue::Number { text, .. } => write!(f, "{}", text),
+            VariableValue::Boolean(value) => write!(f, "{}", value),
+            VariableValue::String(string) => write!(f, "{}", string),
+        }
+    }
+}
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Variable {
     pub id: String,
-    pub initial_value: String,
+    pub initial_value: VariableValue,
 }
 
 impl From<&str> for Variable {
@@ -13,9 +124,9 @@ impl From<&str> for Variable {
         let id = id_value[0].replace("VAR ", "");
 
         let initial_value = if id_value.len() == 1 {
-            "".to_string()
+            VariableValue::default()
         } else {
-            id_value[1..].join("")
+            VariableValue::parse(&id_value[1..].join("\n"))
         };
 
         Variable { id, initial_value }
@@ -30,7 +141,7 @@ impl ToString for Variable {
 
 #[cfg(test)]
 mod test {
-    use crate::Variable;
+    use crate::{Variable, VariableValue};
     use alloc::string::ToString;
 
     #[test]
@@ -39,7 +150,10 @@ mod test {
             Variable::from("VAR a\n42"),
             Variable {
                 id: "a".to_string(),
-                initial_value: "42".to_string()
+                initial_value: VariableValue::Number {
+                    value: 42.0,
+                    text: "42".to_string()
+                }
             }
         );
     }
@@ -48,10 +162,41 @@ mod test {
     fn variable_to_string() {
         let output = Variable {
             id: "c".to_string(),
-            initial_value: "57".to_string(),
+            initial_value: VariableValue::from(57.0),
         }
         .to_string();
         let expected = "VAR c\n57".to_string();
         assert_eq!(output, expected);
     }
+
+    #[test]
+    fn multi_line_variable() {
+        let variable = Variable::from("VAR a\nhello\nworld");
+        assert_eq!(
+            variable.initial_value,
+            VariableValue::String("hello\nworld".to_string())
+        );
+        assert_eq!(variable.to_string(), "VAR a\nhello\nworld");
+    }
+
+    #[test]
+    fn variable_values() {
+        assert_eq!(VariableValue::parse("true"), VariableValue::Boolean(true));
+        assert_eq!(VariableValue::parse("False").as_number(), None);
+        assert_eq!(VariableValue::parse("-1.50").as_number(), Some(-1.5));
+        assert_eq!(VariableValue::parse("-1.50").to_string(), "-1.50");
+        assert_eq!(VariableValue::parse("1e3").as_number(), Some(1000.0));
+        assert_eq!(
+            VariableValue::parse("inf"),
+            VariableValue::String("inf".to_string())
+        );
+        assert_eq!(
+            VariableValue::parse("e"),
+            VariableValue::String("e".to_string())
+        );
+        assert!(!VariableValue::parse("0").as_bool());
+        assert!(VariableValue::parse("0.1").as_bool());
+        assert!(!VariableValue::parse("").as_bool());
+        assert_eq!(VariableValue::from(true).as_number(), Some(1.0));
+    }
 }
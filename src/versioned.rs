@@ -0,0 +1,375 @@
+use crate::*;
+use alloc::{format, string::String, vec::Vec};
+
+impl Game {
+    /// the game in the format a Bitsy release expects: its header flags,
+    /// room format, properties and dialogue ID conventions.
+    ///
+    /// newer formats are reached with the `migrate_to` steps. for older ones,
+    /// anything the release can't represent is left out with a warning.
+    pub fn to_string_for(&self, version: Version) -> (String, Vec<Diagnostic>) {
+        let mut game = self.clone();
+        let mut warnings = Vec::new();
+        game.version = Some(version);
+
+        if version.supports(Feature::HeaderFlags) {
+            game.add_header_flags(version);
+        } else {
+            if game
+                .flags
+                .iter()
+                .any(|f| !f.name.starts_with("VER_") && f.value != "0")
+            {
+                let message = Feature::HeaderFlags.unsupported_by(version);
+                warnings.push(Diagnostic::warning(EntityKind::Game, "", "flags", message));
+            }
+            game.flags.clear();
+        }
+
+        if version.supports(Feature::RoomKeyword) {
+            game.rename_sets();
+        } else {
+            game.room_type = RoomType::Set;
+        }
+
+        if version.supports(Feature::CommaRoomFormat) {
+            game.room_format = Some(RoomFormat::CommaSeparated);
+        } else {
+            let long_ids = game
+                .rooms
+                .iter()
+                .any(|room| room.tiles.iter().any(|id| id.chars().count() > 1));
+            if long_ids {
+                let message = format!(
+                    "tile IDs longer than one character need the {}",
+                    Feature::CommaRoomFormat
+                );
+                warnings.push(Diagnostic::warning(
+                    EntityKind::Game,
+                    "",
+                    "room_format",
+                    message,
+                ));
+            } else {
+                game.room_format = Some(RoomFormat::Contiguous);
+            }
+        }
+
+        if version.supports(Feature::TileWalls) {
+            game.move_walls_to_tiles();
+        } else {
+            game.move_walls_to_rooms();
+        }
+
+        if version.supports(Feature::SpriteDialogueIds) {
+            game.add_sprite_dialogue_ids();
+        } else {
+            for sprite in &mut game.sprites {
+                let Some(id) = sprite.dialogue_id.take() else {
+                    continue;
+                };
+                if id != sprite.id {
+                    let message = format!(
+                        "dialogue {}: {}",
+                        id,
+                        Feature::SpriteDialogueIds.unsupported_by(version)
+                    );
+                    warnings.push(Diagnostic::warning(
+                        EntityKind::Sprite,
+                        &sprite.id,
+                        "dialogue_id",
+                        message,
+                    ));
+                }
+            }
+        }
+
+        if !version.supports(Feature::DialogueEndings) {
+            game.move_endings_out_of_dialogues();
+        }
+
+        if !version.supports(Feature::ColourIds) {
+            game.remove_colour_ids(version, &mut warnings);
+        }
+
+        if !version.supports(Feature::Fonts) && game.font != Font::AsciiSmall {
+            let message = Feature::Fonts.unsupported_by(version);
+            warnings.push(Diagnostic::warning(EntityKind::Game, "", "font", message));
+            game.font = Font::AsciiSmall;
+            game.custom_font = None;
+            game.font_data = None;
+        }
+
+        let transitions = version.supports(Feature::ExitTransitions);
+        let exit_dialogue = version.supports(Feature::ExitDialogue);
+        for room in &mut game.rooms {
+            for exit in &mut room.exits {
+                if !transitions
+                    && exit
+                        .transition
+                        .take()
+                        .is_some_and(|t| t != Transition::None)
+                {
+                    let message = format!(
+                        "exit at {}: {}",
+                        exit.position,
+                        Feature::ExitTransitions.unsupported_by(version)
+                    );
+                    warnings.push(Diagnostic::warning(
+                        EntityKind::Room,
+                        &room.id,
+                        "exits",
+                        message,
+                    ));
+                }
+                if !exit_dialogue && exit.dialogue_id.take().is_some() {
+                    let message = format!(
+                        "exit at {}: {}",
+                        exit.position,
+                        Feature::ExitDialogue.unsupported_by(version)
+                    );
+                    warnings.push(Diagnostic::warning(
+                        EntityKind::Room,
+                        &room.id,
+                        "exits",
+                        message,
+                    ));
+                }
+            }
+        }
+
+        if !version.supports(Feature::TextDirection)
+            && game.text_direction == TextDirection::RightToLeft
+        {
+            let message = Feature::TextDirection.unsupported_by(version);
+            warnings.push(Diagnostic::warning(
+                EntityKind::Game,
+                "",
+                "text_direction",
+                message,
+            ));
+            game.text_direction = TextDirection::LeftToRight;
+        }
+
+        if game.used_features().contains(&Feature::HdImages) {
+            let message = Feature::HdImages.unsupported_by(version);
+            warnings.push(Diagnostic::warning(EntityKind::Game, "", "images", message));
+        }
+
+        (game.to_string(), warnings)
+    }
+
+    /// the reverse of `move_walls_to_tiles`: each room lists the wall tiles it uses
+    fn move_walls_to_rooms(&mut self) {
+        for room in &mut self.rooms {
+            let mut walls: Vec<String> = Vec::new();
+            let listed = room.walls.take().unwrap_or_default();
+            for id in listed.iter().chain(&room.tiles) {
+                let Some(tile) = self.tiles.iter().find(|t| &t.id == id) else {
+                    continue;
+                };
+                let is_wall = match tile.wall {
+                    Some(wall) => wall,
+                    None => listed.contains(id),
+                };
+                if is_wall && !walls.contains(id) {
+                    walls.push(id.clone());
+                }
+            }
+            if !walls.is_empty() {
+                room.walls = Some(walls);
+            }
+        }
+        for tile in &mut self.tiles {
+            tile.wall = None;
+        }
+    }
+
+    /// room endings that show a dialogue get an `END` with the dialogue's text instead.
+    /// the dialogue is removed unless something else still shows it.
+    fn move_endings_out_of_dialogues(&mut self) {
+        for dialogue_id in self.dialogue_endings() {
+            let ending_id = if self.ending_ids().contains(&dialogue_id) {
+                self.new_ending_id()
+            } else {
+                dialogue_id.clone()
+            };
+            let Some(index) = self.dialogues.iter().position(|d| d.id == dialogue_id) else {
+                continue;
+            };
+            self.endings.push(Ending {
+                id: ending_id.clone(),
+                dialogue: self.dialogues[index].contents.clone(),
+            });
+            for ending in self.rooms.iter_mut().flat_map(|room| &mut room.endings) {
+                if ending.id == dialogue_id {
+                    ending.id = ending_id.clone();
+                }
+            }
+
+            let still_used = self
+                .sprites
+                .iter()
+                .any(|s| self.sprite_dialogue_id(s) == Some(&dialogue_id))
+                || self
+                    .items
+                    .iter()
+                    .any(|i| i.dialogue_id.as_ref() == Some(&dialogue_id))
+                || self
+                    .rooms
+                    .iter()
+                    .flat_map(|room| &room.exits)
+                    .any(|e| e.dialogue_id.as_ref() == Some(&dialogue_id));
+            if !still_used {
+                self.dialogues.remove(index);
+            }
+        }
+    }
+
+    fn remove_colour_ids(&mut self, version: Version, warnings: &mut Vec<Diagnostic>) {
+        let message = || Feature::ColourIds.unsupported_by(version);
+        for tile in &mut self.tiles {
+            if tile.colour_id.take().is_some() {
+                warnings.push(Diagnostic::warning(
+                    EntityKind::Tile,
+                    &tile.id,
+                    "colour_id",
+                    message(),
+                ));
+            }
+        }
+        for sprite in &mut self.sprites {
+            if sprite.colour_id.take().is_some() {
+                warnings.push(Diagnostic::warning(
+                    EntityKind::Sprite,
+                    &sprite.id,
+                    "colour_id",
+                    message(),
+                ));
+            }
+        }
+        for item in &mut self.items {
+            if item.colour_id.take().is_some() {
+                warnings.push(Diagnostic::warning(
+                    EntityKind::Item,
+                    &item.id,
+                    "colour_id",
+                    message(),
+                ));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn same_version() {
+        let game = mock::game_default();
+        let (output, warnings) = game.to_string_for(game.version());
+        assert_eq!(output, game.to_string());
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn bitsy_8() {
+        let game = mock::game_default();
+        let (output, warnings) = game.to_string_for(Version {
+            major: 8,
+            minor: 14,
+        });
+        assert!(output.contains("\n! VER_MAJ 8\n! VER_MIN 14\n! ROOM_FORMAT 1\n"));
+        assert_eq!(warnings, vec![]);
+
+        let game = Game::from(include_str!("test-resources/default-v8.14.bitsy")).unwrap();
+        let (output, warnings) = game.to_string_for(mock::game_default().version());
+        assert!(output.starts_with(
+            "Write your game's title here\n\n# BITSY VERSION 7.10\n\n! ROOM_FORMAT 1\n\nPAL 0"
+        ));
+        assert_eq!(warnings, vec![]);
+    }
+
+    #[test]
+    fn legacy_format() {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game.rooms[0].exits.push(ExitInstance {
+            position: Position { x: 2, y: 2 },
+            exit: Exit {
+                room_id: "0".to_string(),
+                position: Position { x: 3, y: 3 },
+                effect: Transition::None,
+            },
+            transition: Some(Transition::Wave),
+            dialogue_id: None,
+        });
+        game.text_direction = TextDirection::RightToLeft;
+
+        let (output, warnings) = game.to_string_for(Version { major: 3, minor: 0 });
+        assert!(output.contains("\nROOM 0\n0000000000000000\n0aaaaaaaaaaaaaa0\n"));
+        assert!(output.contains("\nWAL a\n"));
+        assert!(!output.contains("WAL true"));
+        assert!(!output.contains("TEXT_DIRECTION"));
+        assert!(!output.contains(" FX wave"));
+
+        let warnings: Vec<String> = warnings.iter().map(|w| w.to_string()).collect();
+        assert_eq!(
+            warnings,
+            vec![
+                "warning: sprite a dialogue_id: dialogue 0: Bitsy 3.0 doesn't support sprite dialogue IDs",
+                "warning: room 0 exits: exit at 2,2: Bitsy 3.0 doesn't support exit transitions",
+                "warning: game text_direction: Bitsy 3.0 doesn't support text direction",
+            ]
+        );
+
+        let (output, _) = game.to_string_for(Version { major: 2, minor: 0 });
+        assert!(output.contains("\nSET 0\n"));
+        let game = Game::from(&output).unwrap();
+        assert!(game.is_wall(&game.rooms[0], Position { x: 1, y: 1 }));
+    }
+
+    #[test]
+    fn dialogue_endings() {
+        let mut game = mock::game_default();
+        game.endings.clear();
+        game.dialogues.push(Dialogue {
+            id: "e".to_string(),
+            contents: "the end".to_string(),
+            name: None,
+        });
+        game.rooms[0].endings = vec![Instance {
+            position: Position { x: 4, y: 4 },
+            id: "e".to_string(),
+        }];
+        assert!(game.used_features().contains(&Feature::DialogueEndings));
+
+        let version = Version {
+            major: 7,
+            minor: 10,
+        };
+        let (output, warnings) = game.to_string_for(version);
+        assert_eq!(warnings, vec![]);
+        assert!(output.contains("\nEND e\nthe end\n"));
+        assert!(!output.contains("\nDLG e\n"));
+        let game = Game::from(&output).unwrap();
+        assert_eq!(game.endings[0].dialogue, "the end");
+        assert_eq!(game.rooms[0].endings[0].id, "e");
+        assert!(!game.used_features().contains(&Feature::DialogueEndings));
+    }
+
+    #[test]
+    fn upgrade_legacy() {
+        let game = Game::from(include_str!("test-resources/legacy.bitsy")).unwrap();
+        let version = Version { major: 7, minor: 0 };
+        let (output, warnings) = game.to_string_for(version);
+        let mut migrated = game.clone();
+        migrated.migrate_to(version).unwrap();
+        assert_eq!(output, migrated.to_string());
+        assert_eq!(warnings, vec![]);
+    }
+}
@@ -0,0 +1,213 @@
+use crate::*;
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+/// which tiles of a room the avatar can walk on
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WalkMap {
+    blocked: [bool; 256],
+    /// exits and endings: walking onto them leaves the room
+    stops: [bool; 256],
+}
+
+fn index(position: Position) -> usize {
+    usize::from(position.y) * 16 + usize::from(position.x)
+}
+
+fn position(index: usize) -> Position {
+    Position {
+        x: (index % 16) as u8,
+        y: (index / 16) as u8,
+    }
+}
+
+impl WalkMap {
+    /// walls (tiles with `WAL true` or listed in the legacy room walls)
+    /// and sprites other than the avatar block movement.
+    pub fn new(game: &Game, room: &Room) -> Self {
+        let mut map = WalkMap {
+            blocked: [false; 256],
+            stops: [false; 256],
+        };
+        for i in 0..256 {
+            map.blocked[i] = game.is_wall(room, position(i));
+        }
+        for sprite in &game.sprites {
+            if sprite.id == "A" || sprite.room_id.as_ref() != Some(&room.id) {
+                continue;
+            }
+            if let Some(position) = sprite.position.filter(|p| p.in_bounds()) {
+                map.blocked[index(position)] = true;
+            }
+        }
+        let stops = room.exits.iter().map(|exit| exit.position);
+        let stops = stops.chain(room.endings.iter().map(|ending| ending.position));
+        for position in stops.filter(|p| p.in_bounds()) {
+            map.stops[index(position)] = true;
+        }
+        map
+    }
+
+    pub fn is_walkable(&self, position: Position) -> bool {
+        position.in_bounds() && !self.blocked[index(position)]
+    }
+
+    pub fn set_walkable(&mut self, position: Position, walkable: bool) {
+        if position.in_bounds() {
+            self.blocked[index(position)] = !walkable;
+        }
+    }
+
+    /// whether walking onto the position (an exit or ending) leaves the room
+    pub fn is_stop(&self, position: Position) -> bool {
+        position.in_bounds() && self.stops[index(position)]
+    }
+
+    /// breadth-first search from the position, returning the previous step
+    /// on a shortest path for every visited position
+    fn search(&self, from: Position) -> [Option<usize>; 256] {
+        let mut previous = [None; 256];
+        if !from.in_bounds() {
+            return previous;
+        }
+        previous[index(from)] = Some(index(from));
+        let mut queue = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            // the avatar can start on an exit but walking onto one leaves the room
+            if current != from && self.is_stop(current) {
+                continue;
+            }
+            for direction in Direction::ALL {
+                let Some(next) = current.step(direction) else {
+                    continue;
+                };
+                if self.is_walkable(next) && previous[index(next)].is_none() {
+                    previous[index(next)] = Some(index(current));
+                    queue.push_back(next);
+                }
+            }
+        }
+        previous
+    }
+
+    /// all positions the avatar can walk to, including the starting position.
+    /// the avatar can start anywhere but can't walk through walls, sprites, exits and endings.
+    pub fn reachable_from(&self, from: Position) -> Vec<Position> {
+        let previous = self.search(from);
+        (0..256)
+            .filter(|&i| previous[i].is_some())
+            .map(position)
+            .collect()
+    }
+
+    /// a shortest path between the positions, including both of them
+    pub fn find_path(&self, from: Position, to: Position) -> Option<Vec<Position>> {
+        if !from.in_bounds() || !to.in_bounds() {
+            return None;
+        }
+        let previous = self.search(from);
+        let mut current = index(to);
+        let mut path = Vec::from([to]);
+        while current != index(from) {
+            current = previous[current]?;
+            path.push(position(current));
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+impl Game {
+    pub fn walk_map(&self, room_id: &str) -> Option<WalkMap> {
+        self.get_room(room_id).map(|room| WalkMap::new(self, room))
+    }
+}
+
+/// the moves needed to follow a path
+pub fn path_directions(path: &[Position]) -> Vec<Direction> {
+    path.windows(2)
+        .filter_map(|pair| {
+            Direction::ALL
+                .into_iter()
+                .find(|&direction| pair[0].step(direction) == Some(pair[1]))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::mock;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    /// the default room with a wall ring around 1,1 .. 14,14
+    fn game() -> Game {
+        let mut game = mock::game_default();
+        game.tiles[0].wall = Some(true);
+        game
+    }
+
+    #[test]
+    fn walk_map() {
+        let map = game().walk_map("0").unwrap();
+        assert!(map.is_walkable(Position { x: 0, y: 0 }));
+        assert!(!map.is_walkable(Position { x: 1, y: 1 }));
+        // the cat sprite
+        assert!(!map.is_walkable(Position { x: 8, y: 12 }));
+        // the avatar
+        assert!(map.is_walkable(Position { x: 4, y: 4 }));
+        assert!(!map.is_walkable(Position { x: 16, y: 0 }));
+    }
+
+    #[test]
+    fn find_path() {
+        let map = game().walk_map("0").unwrap();
+        let from = Position { x: 4, y: 4 };
+        let path = map.find_path(from, Position { x: 6, y: 3 }).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path[0], from);
+        assert_eq!(
+            path_directions(&path),
+            vec![Direction::Up, Direction::Right, Direction::Right]
+        );
+        assert_eq!(map.find_path(from, from), Some(vec![from]));
+        // outside of the walls
+        assert_eq!(map.find_path(from, Position { x: 0, y: 0 }), None);
+        // 16,0 would be the same cell as 0,1 if it weren't checked
+        let outside = Position { x: 16, y: 0 };
+        assert_eq!(map.find_path(outside, Position { x: 0, y: 1 }), None);
+        assert_eq!(map.find_path(from, outside), None);
+    }
+
+    #[test]
+    fn reachable_from() {
+        let map = game().walk_map("0").unwrap();
+        // 12x12 inside the walls, minus the cat
+        assert_eq!(map.reachable_from(Position { x: 4, y: 4 }).len(), 143);
+    }
+
+    #[test]
+    fn exits_stop_walking() {
+        let mut game = game();
+        game.rooms[0].exits.push(ExitInstance {
+            position: Position { x: 2, y: 3 },
+            exit: Exit {
+                room_id: "0".to_string(),
+                position: Position { x: 0, y: 0 },
+                effect: Transition::None,
+            },
+            transition: None,
+            dialogue_id: None,
+        });
+        for x in 3..14 {
+            game.rooms[0].tiles[3 * 16 + x] = "a".to_string();
+        }
+        let map = game.walk_map("0").unwrap();
+        assert!(map.is_stop(Position { x: 2, y: 3 }));
+        // the only way to the top row is through the exit
+        let top = Position { x: 5, y: 2 };
+        assert_eq!(map.find_path(Position { x: 4, y: 4 }, top), None);
+        assert!(map.find_path(top, Position { x: 2, y: 3 }).is_some());
+    }
+}